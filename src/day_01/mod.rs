@@ -1,43 +1,222 @@
-use regex::{self, Regex};
+use std::{cmp::Reverse, collections::BinaryHeap, fmt, io::BufRead};
 
-fn parse_calorie_line(calorie_line: &str) -> u32 {
-    match calorie_line.trim().parse() {
-        Ok(val) => val,
-        Err(_) => 0,
-    }
+pub fn day_01<R: BufRead>(reader: R) -> Result<u64, String> {
+    return sum_top_calories(reader, 1);
 }
 
-fn compute_per_elf_calories(calorie_strings: &str) -> u32 {
-    let calorie_split_regex = Regex::new(r"[[:space:]]+").unwrap();
-    let cals: u32 = calorie_split_regex
-        .split(calorie_strings)
-        .into_iter()
-        .map(parse_calorie_line)
-        .sum();
-    return cals;
+pub fn day_01_b<R: BufRead>(reader: R) -> Result<u64, String> {
+    return sum_top_calories(reader, 3);
+}
+
+fn sum_top_calories<R: BufRead>(reader: R, n: usize) -> Result<u64, String> {
+    return stream_top_calories(reader, n)?
+        .iter()
+        .try_fold(0u64, |sum, (_, total)| sum.checked_add(*total))
+        .ok_or(format!("Sum of the top {} calorie totals overflows", n));
 }
 
-pub fn day_01(input: String) -> u32 {
-    let per_elf_pattern = Regex::new(r"\n[[:space:]]*\n").unwrap();
-    let max_cals: u32 = per_elf_pattern
-        .split(input.as_str())
+// Reads one line at a time and only keeps the best `n` (elf number, total)
+// pairs in a min-heap, so memory doesn't grow with the size of the input.
+// Invalid lines are counted rather than kept for the same reason.
+pub fn stream_top_calories<R: BufRead>(reader: R, n: usize) -> Result<Vec<(usize, u64)>, String> {
+    let mut heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>> = BinaryHeap::with_capacity(n + 1);
+    let mut first_invalid: Option<InvalidLine> = None;
+    let mut invalid_count = 0;
+    read_elves(
+        reader,
+        |elf| {
+            heap.push(Reverse((elf.total, Reverse(elf.number))));
+            if heap.len() > n {
+                heap.pop();
+            }
+        },
+        |bad| {
+            first_invalid.get_or_insert(bad);
+            invalid_count += 1;
+        },
+    )?;
+
+    if let Some(bad) = first_invalid {
+        return Err(invalid_lines_error(&bad, invalid_count));
+    }
+    return Ok(heap
+        .into_sorted_vec()
         .into_iter()
-        .map(compute_per_elf_calories)
-        .max()
-        .unwrap_or(0);
+        .map(|Reverse((total, Reverse(number)))| (number, total))
+        .collect());
+}
 
-    return max_cals;
+// Blank lines separate elves. Every numbered elf goes to `on_elf` once its
+// items are all read, and anything that isn't a number goes to
+// `on_invalid` instead of counting as zero. Only the running total of the
+// current elf is kept, and it is an error for it to overflow.
+fn read_elves<R: BufRead>(
+    mut reader: R,
+    mut on_elf: impl FnMut(Elf),
+    mut on_invalid: impl FnMut(InvalidLine),
+) -> Result<(), String> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut elf_count = 0;
+    // The current elf's total, once it has any items.
+    let mut total: Option<u64> = None;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        line_number += 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(total) = total.take() {
+                elf_count += 1;
+                on_elf(Elf {
+                    number: elf_count,
+                    total,
+                });
+            }
+            if read == 0 {
+                return Ok(());
+            }
+            continue;
+        }
+        match trimmed.parse() {
+            Ok(val) => {
+                let sum = total.unwrap_or(0).checked_add(val);
+                total =
+                    Some(sum.ok_or(format!("Calorie total of elf {} overflows", elf_count + 1))?);
+            }
+            Err(_) => on_invalid(InvalidLine {
+                line_number,
+                text: trimmed.to_string(),
+            }),
+        }
+    }
 }
 
-pub fn day_01_b(input: String) -> u32 {
-    let per_elf_pattern = Regex::new(r"\n[[:space:]]*\n").unwrap();
-    let mut per_elf_calories: Vec<u32> = per_elf_pattern
-        .split(input.as_str())
-        .into_iter()
-        .map(compute_per_elf_calories)
-        .collect();
-    per_elf_calories.sort();
-    return per_elf_calories.into_iter().rev().take(3).sum();
+fn invalid_lines_error(first: &InvalidLine, count: usize) -> String {
+    return format!(
+        "Line {} is not a calorie count: {:?} ({} invalid lines)",
+        first.line_number, first.text, count
+    );
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Elf {
+    pub number: usize,
+    pub total: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidLine {
+    pub line_number: usize,
+    pub text: String,
+}
+
+pub struct CalorieInventory {
+    elves: Vec<Elf>,
+    invalid_lines: Vec<InvalidLine>,
+    // Every elf's calories together, for the mean.
+    total: u64,
+}
+
+impl CalorieInventory {
+    // Same rules as `stream_top_calories`, but every elf and invalid line is
+    // kept for the statistics.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut elves: Vec<Elf> = Vec::new();
+        let mut invalid_lines: Vec<InvalidLine> = Vec::new();
+        read_elves(
+            input.as_bytes(),
+            |e| elves.push(e),
+            |bad| invalid_lines.push(bad),
+        )?;
+        let total = elves
+            .iter()
+            .try_fold(0u64, |sum, e| sum.checked_add(e.total))
+            .ok_or("Calorie total of all elves overflows".to_string())?;
+        return Ok(Self {
+            elves,
+            invalid_lines,
+            total,
+        });
+    }
+
+    pub fn validated(self) -> Result<Self, String> {
+        return match self.invalid_lines.first() {
+            Some(bad) => Err(invalid_lines_error(bad, self.invalid_lines.len())),
+            None => Ok(self),
+        };
+    }
+
+    pub fn elves(&self) -> &[Elf] {
+        return &self.elves;
+    }
+
+    pub fn invalid_lines(&self) -> &[InvalidLine] {
+        return &self.invalid_lines;
+    }
+
+    // Highest totals first; ties keep the lower elf number first.
+    pub fn top(&self, n: usize) -> Vec<&Elf> {
+        let mut sorted: Vec<&Elf> = self.elves.iter().collect();
+        sorted.sort_by_key(|e| Reverse(e.total));
+        sorted.truncate(n);
+        return sorted;
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.elves.is_empty() {
+            return None;
+        }
+        return Some(self.total as f64 / self.elves.len() as f64);
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        let mut totals: Vec<u64> = self.elves.iter().map(|e| e.total).collect();
+        totals.sort();
+        let mid = totals.len() / 2;
+        return match totals.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(totals[mid] as f64),
+            _ => Some((totals[mid - 1] as f64 + totals[mid] as f64) / 2.0),
+        };
+    }
+
+    // Elf counts per bucket of totals, keyed by the bucket's lower bound.
+    pub fn distribution(&self, bucket_size: u64) -> Vec<(u64, usize)> {
+        let bucket_size = bucket_size.max(1);
+        let max_total = self.elves.iter().map(|e| e.total).max().unwrap_or(0);
+        let mut buckets = vec![0; (max_total / bucket_size) as usize + 1];
+        for e in &self.elves {
+            buckets[(e.total / bucket_size) as usize] += 1;
+        }
+        return buckets
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (i as u64 * bucket_size, count))
+            .collect();
+    }
+}
+
+impl fmt::Display for CalorieInventory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Elves: {}", self.elves().len())?;
+        for (rank, elf) in self.top(3).iter().enumerate() {
+            writeln!(f, "#{}: elf {} with {}", rank + 1, elf.number, elf.total)?;
+        }
+        writeln!(
+            f,
+            "Mean: {:.1} Median: {:.1}",
+            self.mean().unwrap_or(0.0),
+            self.median().unwrap_or(0.0)
+        )?;
+        for (start, count) in self.distribution(10000) {
+            writeln!(f, "{:>6}+: {}", start, "#".repeat(count))?;
+        }
+        for bad in self.invalid_lines() {
+            writeln!(f, "Invalid line {}: {:?}", bad.line_number, bad.text)?;
+        }
+        return Ok(());
+    }
 }
 
 #[cfg(test)]
@@ -63,8 +242,8 @@ mod tests {
             10000
         "#;
 
-        let result = day_01(input.to_string());
-        assert_eq!(result, 24000);
+        let result = day_01(input.as_bytes());
+        assert_eq!(result, Ok(24000));
     }
 
     #[test]
@@ -86,7 +265,152 @@ mod tests {
             10000
         "#;
 
-        let result = day_01_b(input.to_string());
-        assert_eq!(result, 45000);
+        let result = day_01_b(input.as_bytes());
+        assert_eq!(result, Ok(45000));
+    }
+
+    #[test]
+    fn test_inventory_statistics() {
+        let input = r#"
+            1000
+            2000
+            3000
+
+            4000
+
+            5000
+            6000
+
+            7000
+            8000
+            9000
+
+            10000
+        "#;
+
+        let inventory = CalorieInventory::parse(input).unwrap();
+        assert_eq!(inventory.elves().len(), 5);
+        let top: Vec<(usize, u64)> = inventory
+            .top(2)
+            .iter()
+            .map(|e| (e.number, e.total))
+            .collect();
+        assert_eq!(top, vec![(4, 24000), (3, 11000)]);
+        assert_eq!(inventory.top(10).len(), 5);
+        assert_eq!(inventory.mean(), Some(11000.0));
+        assert_eq!(inventory.median(), Some(10000.0));
+        assert_eq!(
+            inventory.distribution(10000),
+            vec![(0, 2), (10000, 2), (20000, 1)]
+        );
+    }
+
+    #[test]
+    fn test_unparsable_lines_are_flagged() {
+        let input = "1000\n2OOO\n\n3000\n";
+        let inventory = CalorieInventory::parse(input).unwrap();
+        assert_eq!(
+            inventory.invalid_lines(),
+            &[InvalidLine {
+                line_number: 2,
+                text: "2OOO".to_string()
+            }]
+        );
+        assert!(day_01(input.as_bytes()).is_err());
+    }
+
+    // Both parsers share one reader, so they must agree on what's invalid.
+    #[test]
+    fn test_stream_and_inventory_agree_on_invalid_lines() {
+        let input = "  \n1000\n2OOO\n\n3000\nlots\n";
+        let expected = "Line 3 is not a calorie count: \"2OOO\" (2 invalid lines)".to_string();
+        assert_eq!(
+            CalorieInventory::parse(input).unwrap().validated().err(),
+            Some(expected.clone())
+        );
+        assert_eq!(stream_top_calories(input.as_bytes(), 3), Err(expected));
+
+        let input = "1000\n2000\n\n3000\n";
+        let inventory = CalorieInventory::parse(input).unwrap().validated().unwrap();
+        let from_inventory: Vec<(usize, u64)> = inventory
+            .top(2)
+            .iter()
+            .map(|e| (e.number, e.total))
+            .collect();
+        assert_eq!(stream_top_calories(input.as_bytes(), 2), Ok(from_inventory));
+    }
+
+    #[test]
+    fn test_stream_total_overflow() {
+        let input = format!("{}\n1\n", u64::MAX);
+        assert_eq!(
+            stream_top_calories(input.as_bytes(), 1),
+            Err("Calorie total of elf 1 overflows".to_string())
+        );
+        let input = "9223372036854775808\n\n9223372036854775808\n\n1\n";
+        assert_eq!(
+            day_01_b(input.as_bytes()),
+            Err("Sum of the top 3 calorie totals overflows".to_string())
+        );
+        assert_eq!(day_01(input.as_bytes()), Ok(9223372036854775808));
+    }
+
+    #[test]
+    fn test_inventory_total_overflow() {
+        let input = format!("{}\n1\n", u64::MAX);
+        assert_eq!(
+            CalorieInventory::parse(&input).err(),
+            Some("Calorie total of elf 1 overflows".to_string())
+        );
+        let input = format!("{}\n\n1\n", u64::MAX);
+        assert_eq!(
+            CalorieInventory::parse(&input).err(),
+            Some("Calorie total of all elves overflows".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stream_top_calories() {
+        let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+        let top = stream_top_calories(input.as_bytes(), 3).unwrap();
+        assert_eq!(top, vec![(4, 24000), (3, 11000), (5, 10000)]);
+        assert_eq!(stream_top_calories(input.as_bytes(), 0).unwrap(), vec![]);
+        assert!(stream_top_calories("1\nx\n".as_bytes(), 1).is_err());
+    }
+
+    #[test]
+    fn test_stream_totals_beyond_u32() {
+        let input = "4000000000\n4000000000\n\n1\n";
+        let top = stream_top_calories(input.as_bytes(), 1).unwrap();
+        assert_eq!(top, vec![(1, 8_000_000_000)]);
+    }
+
+    // Generates elves on the fly so the input is never held in memory.
+    struct GeneratedInventory {
+        elves_left: usize,
+        pending: Vec<u8>,
+    }
+
+    impl std::io::Read for GeneratedInventory {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() && self.elves_left > 0 {
+                self.pending = format!("{}\n{}\n\n", self.elves_left, self.elves_left).into_bytes();
+                self.elves_left -= 1;
+            }
+            let len = self.pending.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            return Ok(len);
+        }
+    }
+
+    #[test]
+    fn test_stream_generated_inventory() {
+        let reader = std::io::BufReader::new(GeneratedInventory {
+            elves_left: 200_000,
+            pending: Vec::new(),
+        });
+        let top = stream_top_calories(reader, 3).unwrap();
+        assert_eq!(top, vec![(1, 400_000), (2, 399_998), (3, 399_996)]);
     }
 }
@@ -1,67 +1,81 @@
+use std::fmt;
+
+mod rules;
+pub use rules::RuleTable;
+
 pub fn rock_paper_scissors(input: String) -> u32 {
-    let score: u32 = input
-        .split('\n')
-        .filter_map(input_line_to_round)
-        .map(|r| r.score())
-        .sum();
-    return score;
+    return analyze_strategy_guide(&input, &RuleTable::classic()).total_score;
 }
 
 pub fn rock_paper_scissors_pt2(input: String) -> u32 {
-    let score: u32 = input
+    return analyze_strategy_guide_pt2(&input, &RuleTable::classic()).total_score;
+}
+
+pub fn analyze_strategy_guide<'a>(input: &str, rules: &'a RuleTable) -> StrategyReport<'a> {
+    let rounds = input
+        .split('\n')
+        .filter_map(|line| input_line_to_round(line, rules))
+        .collect();
+    return StrategyReport::from_rounds(rounds, rules);
+}
+
+pub fn analyze_strategy_guide_pt2<'a>(input: &str, rules: &'a RuleTable) -> StrategyReport<'a> {
+    let rounds = input
         .split('\n')
-        .filter_map(input_line_to_round_pt2)
-        .map(|r| r.score())
-        .sum();
-    return score;
-}
-
-fn input_line_to_round_pt2(line: &str) -> Option<Round> {
-    let splitted: Vec<&str> = line.trim().split(' ').collect();
-    let their_throw = letter_to_throw(splitted[0])?;
-    let my_throw = match splitted[1] {
-        "X" => Some(get_winning_throw_for_opponent(
-            get_winning_throw_for_opponent(their_throw),
-        )), // loose
-        "Y" => Some(their_throw),                                 // draw
-        "Z" => Some(get_winning_throw_for_opponent(their_throw)), // win
-        _ => None,
-    }?;
+        .filter_map(|line| input_line_to_round_pt2(line, rules))
+        .collect();
+    return StrategyReport::from_rounds(rounds, rules);
+}
+
+// Ignores our column and plays the best throw against each opponent throw.
+pub fn analyze_optimal_play<'a>(input: &str, rules: &'a RuleTable) -> StrategyReport<'a> {
+    let rounds = input
+        .split('\n')
+        .filter_map(|line| input_line_to_round(line, rules))
+        .map(|r| best_round_against(r.their_throw, rules))
+        .collect();
+    return StrategyReport::from_rounds(rounds, rules);
+}
+
+pub fn best_possible_score(input: &str, rules: &RuleTable) -> u32 {
+    return analyze_optimal_play(input, rules).total_score;
+}
+
+fn best_round_against(their_throw: Throw, rules: &RuleTable) -> Round {
+    return rules
+        .throws()
+        .map(|my_throw| Round {
+            my_throw,
+            their_throw,
+        })
+        .max_by_key(|r| r.score(rules))
+        .unwrap();
+}
+
+fn input_line_to_round_pt2(line: &str, rules: &RuleTable) -> Option<Round> {
+    let splitted: Vec<&str> = line.split_whitespace().collect();
+    let their_throw = letter_to_throw(splitted.first()?, |l| rules.their_throw(l))?;
+    let outcome = rules.desired_outcome(splitted.get(1)?)?;
     return Some(Round {
         their_throw,
-        my_throw,
+        my_throw: rules.throw_for_outcome(their_throw, outcome)?,
     });
 }
 
-fn get_winning_throw_for_opponent(their_throw: Throw) -> Throw {
-    return match their_throw {
-        Throw::ROCK => Throw::PAPER,
-        Throw::PAPER => Throw::SCISSORS,
-        Throw::SCISSORS => Throw::ROCK,
-    };
-}
-
-fn input_line_to_round(line: &str) -> Option<Round> {
-    let splitted: Vec<&str> = line.trim().split(' ').collect();
+fn input_line_to_round(line: &str, rules: &RuleTable) -> Option<Round> {
+    let splitted: Vec<&str> = line.split_whitespace().collect();
     return Some(Round {
-        their_throw: letter_to_throw(splitted[0])?,
-        my_throw: letter_to_throw(splitted[1])?,
+        their_throw: letter_to_throw(splitted.first()?, |l| rules.their_throw(l))?,
+        my_throw: letter_to_throw(splitted.get(1)?, |l| rules.my_throw(l))?,
     });
 }
 
-fn letter_to_throw(letter: &str) -> Option<Throw> {
-    return match letter.trim() {
-        "A" => Some(Throw::ROCK),
-        "B" => Some(Throw::PAPER),
-        "C" => Some(Throw::SCISSORS),
-        "X" => Some(Throw::ROCK),
-        "Y" => Some(Throw::PAPER),
-        "Z" => Some(Throw::SCISSORS),
-        _ => {
-            println!("Invalid letter: {}", letter);
-            return None;
-        }
-    };
+fn letter_to_throw<F: Fn(&str) -> Option<Throw>>(letter: &str, lookup: F) -> Option<Throw> {
+    let throw = lookup(letter);
+    if throw.is_none() {
+        println!("Invalid letter: {}", letter);
+    }
+    return throw;
 }
 
 #[derive(Debug)]
@@ -71,41 +85,75 @@ struct Round {
 }
 
 impl Round {
-    fn score(&self) -> u32 {
-        let win = 6;
-        let lose = 0;
-        let draw = 3;
-        let outcome = match self.my_throw {
-            Throw::ROCK => match self.their_throw {
-                Throw::ROCK => draw,
-                Throw::PAPER => lose,
-                Throw::SCISSORS => win,
-            },
-            Throw::PAPER => match self.their_throw {
-                Throw::ROCK => win,
-                Throw::PAPER => draw,
-                Throw::SCISSORS => lose,
-            },
-            Throw::SCISSORS => match self.their_throw {
-                Throw::ROCK => lose,
-                Throw::PAPER => win,
-                Throw::SCISSORS => draw,
-            },
+    fn score(&self, rules: &RuleTable) -> u32 {
+        return rules.outcome_points(self.outcome(rules)) + rules.bonus(self.my_throw);
+    }
+
+    fn outcome(&self, rules: &RuleTable) -> Outcome {
+        return rules.outcome(self.my_throw, self.their_throw);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Win,
+    Draw,
+    Lose,
+}
+
+// Index of a shape in a RuleTable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throw(usize);
+
+// Rounds are kept as played and only named through the rule table when the
+// report is printed.
+pub struct StrategyReport<'a> {
+    rules: &'a RuleTable,
+    rounds: Vec<Round>,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub total_score: u32,
+}
+
+impl<'a> StrategyReport<'a> {
+    fn from_rounds(rounds: Vec<Round>, rules: &'a RuleTable) -> Self {
+        let count = |outcome: Outcome| {
+            rounds
+                .iter()
+                .filter(|r| r.outcome(rules) == outcome)
+                .count()
         };
-        let bonus = match self.my_throw {
-            Throw::ROCK => 1,
-            Throw::PAPER => 2,
-            Throw::SCISSORS => 3,
+        return Self {
+            wins: count(Outcome::Win),
+            draws: count(Outcome::Draw),
+            losses: count(Outcome::Lose),
+            total_score: rounds.iter().map(|r| r.score(rules)).sum(),
+            rules,
+            rounds,
         };
-        return outcome + bonus;
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Throw {
-    ROCK,
-    PAPER,
-    SCISSORS,
+impl fmt::Display for StrategyReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, round) in self.rounds.iter().enumerate() {
+            writeln!(
+                f,
+                "Round {}: {} vs {}: {:?} ({})",
+                i + 1,
+                self.rules.name(round.my_throw),
+                self.rules.name(round.their_throw),
+                round.outcome(self.rules),
+                round.score(self.rules)
+            )?;
+        }
+        write!(
+            f,
+            "Wins: {} Draws: {} Losses: {} Total: {}",
+            self.wins, self.draws, self.losses, self.total_score
+        )
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +161,11 @@ mod tests {
     use super::*;
     use test_case::test_case;
 
+    // Positions of the shapes in the classic rules.
+    const ROCK: Throw = Throw(0);
+    const PAPER: Throw = Throw(1);
+    const SCISSORS: Throw = Throw(2);
+
     #[test]
     fn test_sample_input() {
         let input = r#"
@@ -124,15 +177,15 @@ mod tests {
         assert_eq!(score, 15);
     }
 
-    #[test_case(Throw::ROCK, Throw::ROCK, 3, 1)]
-    #[test_case(Throw::ROCK, Throw::PAPER, 0, 1)]
-    #[test_case(Throw::ROCK, Throw::SCISSORS, 6, 1)]
-    #[test_case(Throw::PAPER, Throw::ROCK, 6, 2)]
-    #[test_case(Throw::PAPER, Throw::PAPER, 3, 2)]
-    #[test_case(Throw::PAPER, Throw::SCISSORS, 0, 2)]
-    #[test_case(Throw::SCISSORS, Throw::ROCK, 0, 3)]
-    #[test_case(Throw::SCISSORS, Throw::PAPER, 6, 3)]
-    #[test_case(Throw::SCISSORS, Throw::SCISSORS, 3, 3)]
+    #[test_case(ROCK, ROCK, 3, 1)]
+    #[test_case(ROCK, PAPER, 0, 1)]
+    #[test_case(ROCK, SCISSORS, 6, 1)]
+    #[test_case(PAPER, ROCK, 6, 2)]
+    #[test_case(PAPER, PAPER, 3, 2)]
+    #[test_case(PAPER, SCISSORS, 0, 2)]
+    #[test_case(SCISSORS, ROCK, 0, 3)]
+    #[test_case(SCISSORS, PAPER, 6, 3)]
+    #[test_case(SCISSORS, SCISSORS, 3, 3)]
     fn test_score_calculation(
         my_throw: Throw,
         their_throw: Throw,
@@ -144,7 +197,7 @@ mod tests {
             their_throw: their_throw,
         };
         let expected_score = expected_outcome + expected_bonus;
-        assert_eq!(round.score(), expected_score);
+        assert_eq!(round.score(&RuleTable::classic()), expected_score);
     }
 
     #[test]
@@ -157,4 +210,75 @@ mod tests {
         let score = rock_paper_scissors_pt2(input.to_string());
         assert_eq!(score, 12);
     }
+
+    #[test]
+    fn test_strategy_report() {
+        let input = r#"
+            A Y
+            B X
+            C Z
+        "#;
+        let rules = RuleTable::classic();
+        let report = analyze_strategy_guide(input, &rules);
+        assert_eq!(
+            (report.wins, report.draws, report.losses, report.total_score),
+            (1, 1, 1, 15)
+        );
+        assert_eq!(
+            report.to_string(),
+            "Round 1: Paper vs Rock: Win (8)\n\
+             Round 2: Rock vs Paper: Lose (1)\n\
+             Round 3: Scissors vs Scissors: Draw (6)\n\
+             Wins: 1 Draws: 1 Losses: 1 Total: 15"
+        );
+
+        let throws: Vec<(Throw, Throw)> = report
+            .rounds
+            .iter()
+            .map(|r| (r.my_throw, r.their_throw))
+            .collect();
+        assert_eq!(
+            throws,
+            vec![(PAPER, ROCK), (ROCK, PAPER), (SCISSORS, SCISSORS)]
+        );
+
+        let report_pt2 = analyze_strategy_guide_pt2(input, &rules);
+        assert_eq!(
+            (report_pt2.wins, report_pt2.draws, report_pt2.losses),
+            (1, 1, 1)
+        );
+        assert_eq!(report_pt2.rounds[1].my_throw, ROCK);
+        assert_eq!(report_pt2.total_score, 12);
+    }
+
+    #[test]
+    fn test_best_possible_score() {
+        let input = r#"
+            A Y
+            B X
+            C Z
+        "#;
+        let rules = RuleTable::classic();
+        let report = analyze_optimal_play(input, &rules);
+        assert_eq!((report.wins, report.draws, report.losses), (3, 0, 0));
+        assert_eq!(best_possible_score(input, &rules), 8 + 9 + 7);
+    }
+
+    #[test]
+    fn test_rpsls_strategy_guide() {
+        let rules = RuleTable::from_file("src/day_02/rpsls.txt").unwrap();
+        let input = r#"
+            A W
+            E V
+            D Z
+        "#;
+        let report = analyze_strategy_guide(input, &rules);
+        // Paper covers Rock, Spock vaporizes Rock, Lizard poisons Spock
+        assert_eq!((report.wins, report.draws, report.losses), (1, 0, 2));
+        assert_eq!(report.total_score, (2 + 6) + 1 + 5);
+
+        let report_pt2 = analyze_strategy_guide_pt2("E Z\nE Y", &rules);
+        // Paper is declared before Lizard, so it's the pick to beat Spock
+        assert_eq!(report_pt2.total_score, (2 + 6) + (5 + 3));
+    }
 }
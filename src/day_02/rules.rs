@@ -0,0 +1,277 @@
+use std::{collections::HashSet, fs};
+
+use super::{Outcome, Throw};
+
+const CLASSIC_RULES: &str = r#"
+    shape Rock 1 A X
+    shape Paper 2 B Y
+    shape Scissors 3 C Z
+    beats Rock Scissors
+    beats Paper Rock
+    beats Scissors Paper
+    outcome win 6 Z
+    outcome draw 3 Y
+    outcome lose 0 X
+"#;
+
+// Everything that decides a score: the shapes, who beats whom, and what the
+// letters in each column of the strategy guide mean.
+//
+// Config is one rule per line, `#` starts a comment:
+//     shape <name> <bonus> <their letter> <my letter>
+//     beats <winner> <loser>
+//     outcome <win|draw|lose> <points> <letter>
+#[derive(Debug, Clone)]
+pub struct RuleTable {
+    shapes: Vec<Shape>,
+    beats: Vec<Vec<bool>>,
+    outcomes: Vec<OutcomeRule>,
+}
+
+#[derive(Debug, Clone)]
+struct Shape {
+    name: String,
+    bonus: u32,
+    their_letter: String,
+    my_letter: String,
+}
+
+#[derive(Debug, Clone)]
+struct OutcomeRule {
+    outcome: Outcome,
+    points: u32,
+    letter: String,
+}
+
+impl RuleTable {
+    pub fn classic() -> Self {
+        return Self::parse(CLASSIC_RULES).unwrap();
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        return Self::parse(&text);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        let mut beat_names: Vec<(usize, String, String)> = Vec::new();
+        let mut outcomes: Vec<OutcomeRule> = Vec::new();
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("Rule line {}: {}: {:?}", line_index + 1, msg, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[..] {
+                ["shape", name, bonus, their_letter, my_letter] => shapes.push(Shape {
+                    name: name.to_string(),
+                    bonus: bonus.parse().map_err(|_| err("bad bonus"))?,
+                    their_letter: their_letter.to_string(),
+                    my_letter: my_letter.to_string(),
+                }),
+                ["beats", winner, loser] => {
+                    beat_names.push((line_index + 1, winner.to_string(), loser.to_string()))
+                }
+                ["outcome", outcome, points, letter] => outcomes.push(OutcomeRule {
+                    outcome: match outcome {
+                        "win" => Outcome::Win,
+                        "draw" => Outcome::Draw,
+                        "lose" => Outcome::Lose,
+                        _ => return Err(err("unknown outcome")),
+                    },
+                    points: points.parse().map_err(|_| err("bad points"))?,
+                    letter: letter.to_string(),
+                }),
+                _ => return Err(err("unrecognized rule")),
+            }
+        }
+
+        let mut beats = vec![vec![false; shapes.len()]; shapes.len()];
+        for (line_number, winner, loser) in beat_names {
+            let find = |name: &str| {
+                return shapes.iter().position(|s| s.name == name).ok_or(format!(
+                    "Rule line {}: unknown shape {:?}",
+                    line_number, name
+                ));
+            };
+            beats[find(&winner)?][find(&loser)?] = true;
+        }
+
+        let table = Self {
+            shapes,
+            beats,
+            outcomes,
+        };
+        table.validate()?;
+        return Ok(table);
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.shapes.is_empty() {
+            return Err("Rules define no shapes".to_string());
+        }
+        check_unique("shape name", self.shapes.iter().map(|s| &s.name))?;
+        check_unique("their letter", self.shapes.iter().map(|s| &s.their_letter))?;
+        check_unique("my letter", self.shapes.iter().map(|s| &s.my_letter))?;
+        check_unique("outcome letter", self.outcomes.iter().map(|o| &o.letter))?;
+
+        for a in 0..self.shapes.len() {
+            if self.beats[a][a] {
+                return Err(format!("{} cannot beat itself", self.shapes[a].name));
+            }
+            for b in a + 1..self.shapes.len() {
+                let (a_name, b_name) = (&self.shapes[a].name, &self.shapes[b].name);
+                match (self.beats[a][b], self.beats[b][a]) {
+                    (true, true) => {
+                        return Err(format!("{} and {} beat each other", a_name, b_name))
+                    }
+                    (false, false) => {
+                        return Err(format!("No rule between {} and {}", a_name, b_name))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for outcome in [Outcome::Win, Outcome::Draw, Outcome::Lose] {
+            let count = self
+                .outcomes
+                .iter()
+                .filter(|o| o.outcome == outcome)
+                .count();
+            if count != 1 {
+                return Err(format!("Outcome {:?} defined {} times", outcome, count));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn throws(&self) -> impl Iterator<Item = Throw> {
+        return (0..self.shapes.len()).map(Throw);
+    }
+
+    pub fn name(&self, throw: Throw) -> &str {
+        return &self.shapes[throw.0].name;
+    }
+
+    pub(super) fn their_throw(&self, letter: &str) -> Option<Throw> {
+        return self
+            .shapes
+            .iter()
+            .position(|s| s.their_letter == letter)
+            .map(Throw);
+    }
+
+    pub(super) fn my_throw(&self, letter: &str) -> Option<Throw> {
+        return self
+            .shapes
+            .iter()
+            .position(|s| s.my_letter == letter)
+            .map(Throw);
+    }
+
+    pub(super) fn desired_outcome(&self, letter: &str) -> Option<Outcome> {
+        return self
+            .outcomes
+            .iter()
+            .find(|o| o.letter == letter)
+            .map(|o| o.outcome);
+    }
+
+    pub(super) fn outcome(&self, my_throw: Throw, their_throw: Throw) -> Outcome {
+        if self.beats[my_throw.0][their_throw.0] {
+            return Outcome::Win;
+        }
+        if self.beats[their_throw.0][my_throw.0] {
+            return Outcome::Lose;
+        }
+        return Outcome::Draw;
+    }
+
+    pub(super) fn outcome_points(&self, outcome: Outcome) -> u32 {
+        return self
+            .outcomes
+            .iter()
+            .find(|o| o.outcome == outcome)
+            .unwrap()
+            .points;
+    }
+
+    pub(super) fn bonus(&self, throw: Throw) -> u32 {
+        return self.shapes[throw.0].bonus;
+    }
+
+    // When several throws give the outcome, the one declared first wins.
+    pub(super) fn throw_for_outcome(&self, their_throw: Throw, outcome: Outcome) -> Option<Throw> {
+        return self
+            .throws()
+            .find(|t| self.outcome(*t, their_throw) == outcome);
+    }
+}
+
+impl Default for RuleTable {
+    fn default() -> Self {
+        return Self::classic();
+    }
+}
+
+fn check_unique<'a, I: Iterator<Item = &'a String>>(what: &str, values: I) -> Result<(), String> {
+    let mut seen: HashSet<&String> = HashSet::new();
+    for v in values {
+        if !seen.insert(v) {
+            return Err(format!("Duplicate {}: {}", what, v));
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_classic_rules() {
+        let rules = RuleTable::classic();
+        let rock = rules.my_throw("X").unwrap();
+        let paper = rules.my_throw("Y").unwrap();
+        let scissors = rules.my_throw("Z").unwrap();
+        assert_eq!(rules.name(paper), "Paper");
+        assert_eq!(rules.outcome(rock, scissors), Outcome::Win);
+        assert_eq!(rules.throw_for_outcome(rock, Outcome::Lose), Some(scissors));
+    }
+
+    #[test]
+    fn test_rpsls_rules_file() {
+        let rules = RuleTable::from_file("src/day_02/rpsls.txt").unwrap();
+        assert_eq!(rules.throws().count(), 5);
+        let spock = rules.my_throw("Z").unwrap();
+        let rock = rules.their_throw("A").unwrap();
+        assert_eq!(rules.name(spock), "Spock");
+        assert_eq!(rules.outcome(spock, rock), Outcome::Win);
+    }
+
+    #[test_case("shape Rock 1 A X\nshape Rock 2 B Y", "Duplicate shape name")]
+    #[test_case("shape Rock 1 A X\nshape Paper 2 A Y", "Duplicate their letter")]
+    #[test_case("shape Rock 1 A X\nbeats Rock Paper", "unknown shape")]
+    #[test_case("shape Rock 1 A X\nbeats Rock Rock", "cannot beat itself")]
+    #[test_case("shape Rock 1 A X", "Outcome Win defined 0 times")]
+    #[test_case("shape Rock x A X", "bad bonus")]
+    #[test_case("shoot Rock", "unrecognized rule")]
+    fn test_invalid_rules(text: &str, expected_error: &str) {
+        let err = RuleTable::parse(text).unwrap_err();
+        assert!(err.contains(expected_error), "{}", err);
+    }
+
+    #[test]
+    fn test_inconsistent_beats() {
+        let base = "shape Rock 1 A X\nshape Paper 2 B Y\noutcome win 6 Z\noutcome draw 3 Y\noutcome lose 0 X\n";
+        let both = RuleTable::parse(&(base.to_string() + "beats Rock Paper\nbeats Paper Rock"));
+        assert!(both.unwrap_err().contains("beat each other"));
+        let neither = RuleTable::parse(base);
+        assert!(neither.unwrap_err().contains("No rule between"));
+    }
+}
@@ -1,62 +1,142 @@
-use std::collections::HashSet;
+use std::fmt;
 
-pub fn calc_total_of_high_priority(input: &str) -> u32 {
-    let total = input.lines().map(|l| l.trim()).map(calc_line_total).sum();
-    return total;
+pub fn calc_total_of_high_priority(input: &str) -> Result<u32, String> {
+    let items = analyze_compartments(input)?;
+    return Ok(items.iter().map(|i| i.priority).sum());
 }
 
-pub fn calc_group_badge_totals(input: &str) -> u32 {
-    let cleaned_lines: Vec<&str> = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .collect();
+pub fn calc_group_badge_totals(input: &str) -> Result<u32, String> {
+    let items = analyze_group_badges(input, 3)?;
+    return Ok(items.iter().map(|i| i.priority).sum());
+}
 
-    return cleaned_lines.chunks(3).map(find_badge_letter).sum();
+pub fn analyze_compartments(input: &str) -> Result<Vec<PriorityItem>, String> {
+    let mut items: Vec<PriorityItem> = Vec::new();
+    for (line_index, line) in parse_rucksack_lines(input)? {
+        if line.len() % 2 != 0 {
+            return Err(format!(
+                "Line {} has an odd number of items: {}",
+                line_index, line
+            ));
+        }
+        let compartment_size = line.len() / 2;
+        let left = line_to_mask(&line[..compartment_size]);
+        let right = line_to_mask(&line[compartment_size..]);
+        let item = highest_item(left & right).ok_or(format!(
+            "Line {} has no item in both compartments: {}",
+            line_index, line
+        ))?;
+        items.push(PriorityItem::new(line_index, item));
+    }
+    return Ok(items);
 }
 
-fn find_badge_letter(lines: &[&str]) -> u32 {
-    let mut common: HashSet<char> = HashSet::from_iter(lines[0].chars());
-    for line in lines {
-        let letters: HashSet<char> = HashSet::from_iter(line.chars());
-        common = common.intersection(&letters).cloned().collect();
-    }
-    // let common = lines
-    //     .iter()
-    //     .map(|line| HashSet::from_iter(line.chars()))
-    //     .reduce(|accum, item| accum.intersection(&item).cloned().collect());
-
-    println!("Groups: {:?}", common);
-    let first_letter = common.iter().cloned().next();
-    return match first_letter {
-        Some(l) => letter_to_value(&l),
-        None => 0,
-    };
+pub fn analyze_group_badges(input: &str, group_size: usize) -> Result<Vec<PriorityItem>, String> {
+    if group_size == 0 {
+        return Err("Group size must be at least 1".to_string());
+    }
+    let lines = parse_rucksack_lines(input)?;
+    if lines.len() % group_size != 0 {
+        return Err(format!(
+            "{} rucksacks cannot be split into groups of {}",
+            lines.len(),
+            group_size
+        ));
+    }
+
+    let mut items: Vec<PriorityItem> = Vec::new();
+    for group in lines.chunks(group_size) {
+        let common = group
+            .iter()
+            .map(|(_, line)| line_to_mask(line))
+            .fold(u64::MAX, |acc, mask| acc & mask);
+        let item = highest_item(common).ok_or(format!(
+            "Group starting at line {} has no item in common",
+            group[0].0
+        ))?;
+        items.push(PriorityItem::new(group[0].0, item));
+    }
+    return Ok(items);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PriorityItem {
+    pub line_index: usize,
+    pub item: char,
+    pub priority: u32,
+}
+
+impl PriorityItem {
+    fn new(line_index: usize, item: char) -> Self {
+        return Self {
+            line_index,
+            item,
+            priority: letter_to_value(&item).unwrap(),
+        };
+    }
+}
+
+impl fmt::Display for PriorityItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} ({})",
+            self.line_index, self.item, self.priority
+        )
+    }
+}
+
+// Non-empty lines paired with their index in the input, rejecting anything
+// that isn't a letter so the masks below can't silently drop items.
+fn parse_rucksack_lines(input: &str) -> Result<Vec<(usize, &str)>, String> {
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(bad) = trimmed.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(format!(
+                "Line {} contains invalid item {:?}: {}",
+                line_index, bad, trimmed
+            ));
+        }
+        lines.push((line_index, trimmed));
+    }
+    return Ok(lines);
+}
+
+// Bit (priority - 1) is set for every item in the slice.
+fn line_to_mask(items: &str) -> u64 {
+    return items
+        .chars()
+        .filter_map(|c| letter_to_value(&c))
+        .fold(0, |mask, value| mask | 1 << (value - 1));
 }
 
-fn calc_line_total(line: &str) -> u32 {
-    let compartment_size = line.len() / 2;
-    println!(
-        "Line: {}, len: {}, size: {}",
-        line,
-        line.len(),
-        compartment_size
-    );
-    let left: HashSet<char> = HashSet::from_iter(line.chars().take(compartment_size));
-    let right: HashSet<char> = HashSet::from_iter(line.chars().skip(compartment_size));
-    println!("Left: {:?}, right: {:?}", left, right);
-    return left
-        .intersection(&right)
-        .map(letter_to_value)
-        .max()
-        .unwrap_or(0);
+fn highest_item(mask: u64) -> Option<char> {
+    if mask == 0 {
+        return None;
+    }
+    return value_to_letter(64 - mask.leading_zeros());
 }
 
-fn letter_to_value(letter: &char) -> u32 {
-    if letter.is_lowercase() {
-        return (*letter as u32) - ('a' as u32) + 1;
+fn letter_to_value(letter: &char) -> Option<u32> {
+    if letter.is_ascii_lowercase() {
+        return Some((*letter as u32) - ('a' as u32) + 1);
+    }
+    if letter.is_ascii_uppercase() {
+        return Some((*letter as u32) - ('A' as u32) + 27);
     }
-    return (*letter as u32) - ('A' as u32) + 27;
+    return None;
+}
+
+fn value_to_letter(value: u32) -> Option<char> {
+    return match value {
+        1..=26 => char::from_u32('a' as u32 + value - 1),
+        27..=52 => char::from_u32('A' as u32 + value - 27),
+        _ => None,
+    };
 }
 
 #[cfg(test)]
@@ -76,18 +156,29 @@ mod tests {
         "#;
 
         let total = calc_total_of_high_priority(input);
-        assert_eq!(total, 157);
+        assert_eq!(total, Ok(157));
     }
 
-    #[test_case('a', 1)]
-    #[test_case('z', 26)]
-    #[test_case('A', 27)]
-    #[test_case('Z', 52)]
-    fn test_letter_to_value(letter: char, expected_value: u32) {
+    #[test_case('a', Some(1))]
+    #[test_case('z', Some(26))]
+    #[test_case('A', Some(27))]
+    #[test_case('Z', Some(52))]
+    #[test_case('1', None)]
+    #[test_case('é', None)]
+    fn test_letter_to_value(letter: char, expected_value: Option<u32>) {
         let val = letter_to_value(&letter);
         assert_eq!(val, expected_value);
     }
 
+    #[test_case(1, Some('a'))]
+    #[test_case(26, Some('z'))]
+    #[test_case(27, Some('A'))]
+    #[test_case(52, Some('Z'))]
+    #[test_case(53, None)]
+    fn test_value_to_letter(value: u32, expected_letter: Option<char>) {
+        assert_eq!(value_to_letter(value), expected_letter);
+    }
+
     #[test]
     fn test_badge_value() {
         let input = r#"
@@ -100,6 +191,55 @@ mod tests {
         "#;
 
         let total = calc_group_badge_totals(input);
-        assert_eq!(total, 70);
+        assert_eq!(total, Ok(70));
+    }
+
+    #[test]
+    fn test_compartment_items_report_line() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp\nPmmdzqPrVvPwwTWBwg";
+        let items = analyze_compartments(input).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                PriorityItem {
+                    line_index: 0,
+                    item: 'p',
+                    priority: 16
+                },
+                PriorityItem {
+                    line_index: 1,
+                    item: 'P',
+                    priority: 42
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_badges_with_other_group_sizes() {
+        let input = "abcX\nXdef\nghXi\nYjkl\nmnoY\n";
+        let pairs = analyze_group_badges("abcX\nXdef\nYjkl\nmnoY", 2).unwrap();
+        assert_eq!(pairs.iter().map(|i| i.item).collect::<String>(), "XY");
+        assert!(analyze_group_badges(input, 2).is_err());
+        assert!(analyze_group_badges(input, 0).is_err());
+    }
+
+    #[test]
+    fn test_missing_common_item_is_an_error() {
+        assert_eq!(
+            analyze_compartments("abcd\nabcA"),
+            Err("Line 0 has no item in both compartments: abcd".to_string())
+        );
+        assert_eq!(
+            analyze_group_badges("abcX\nXdef\nYjkl\nmnoZ", 2),
+            Err("Group starting at line 2 has no item in common".to_string())
+        );
+    }
+
+    #[test_case("abc")]
+    #[test_case("ab1d")]
+    #[test_case("ab d")]
+    fn test_invalid_lines_are_rejected(line: &str) {
+        assert!(calc_total_of_high_priority(line).is_err());
     }
 }
@@ -0,0 +1,185 @@
+use std::{fmt, ops::Range};
+
+// Sorted, disjoint, non-adjacent half-open ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet {
+    ranges: Vec<Range<u32>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        return Self { ranges: Vec::new() };
+    }
+
+    pub fn from_ranges<I: IntoIterator<Item = Range<u32>>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        for r in ranges {
+            set.insert(r);
+        }
+        return set;
+    }
+
+    pub fn insert(&mut self, range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut merged = range;
+        let mut kept: Vec<Range<u32>> = Vec::with_capacity(self.ranges.len() + 1);
+        for r in self.ranges.drain(..) {
+            if r.end < merged.start || merged.end < r.start {
+                kept.push(r);
+            } else {
+                merged = merged.start.min(r.start)..merged.end.max(r.end);
+            }
+        }
+        let index = kept.partition_point(|r| r.start < merged.start);
+        kept.insert(index, merged);
+        self.ranges = kept;
+    }
+
+    pub fn ranges(&self) -> &[Range<u32>] {
+        return &self.ranges;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.ranges.is_empty();
+    }
+
+    // Total number of sections covered.
+    pub fn coverage(&self) -> u64 {
+        return self.ranges.iter().map(|r| (r.end - r.start) as u64).sum();
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let index = self.ranges.partition_point(|r| r.end <= value);
+        return index < self.ranges.len() && self.ranges[index].contains(&value);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        return Self::from_ranges(self.ranges.iter().chain(other.ranges.iter()).cloned());
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result: Vec<Range<u32>> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = &self.ranges[i];
+            let b = &other.ranges[j];
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                result.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        return Self { ranges: result };
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result: Vec<Range<u32>> = Vec::new();
+        let mut j = 0;
+        for r in &self.ranges {
+            let mut start = r.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < r.end {
+                let cut = &other.ranges[k];
+                if cut.start > start {
+                    result.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < r.end {
+                result.push(start..r.end);
+            }
+        }
+        return Self { ranges: result };
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet) -> bool {
+        return other.difference(self).is_empty();
+    }
+
+    pub fn overlaps(&self, other: &IntervalSet) -> bool {
+        return !self.intersection(other).is_empty();
+    }
+
+    // Uncovered ranges between the lowest and highest covered section.
+    pub fn gaps(&self) -> Vec<Range<u32>> {
+        return self
+            .ranges
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+            .collect();
+    }
+}
+
+impl From<Range<u32>> for IntervalSet {
+    fn from(range: Range<u32>) -> Self {
+        return Self::from_ranges([range]);
+    }
+}
+
+// Written the way assignments appear in the input: inclusive "a-b" pairs.
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|r| format!("{}-{}", r.start, r.end - 1))
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_insert_merges_overlapping_and_adjacent() {
+        let set = IntervalSet::from_ranges([10..12, 2..5, 4..7, 7..8]);
+        assert_eq!(set.ranges(), &[2..8, 10..12]);
+        assert_eq!(set.coverage(), 8);
+        assert_eq!(set.gaps(), vec![8..10]);
+        assert_eq!(set.to_string(), "2-7,10-11");
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = IntervalSet::from_ranges([1..5, 8..12]);
+        let b = IntervalSet::from_ranges([3..9, 11..15]);
+        assert_eq!(a.union(&b).ranges(), &[1..15]);
+        assert_eq!(a.intersection(&b).ranges(), &[3..5, 8..9, 11..12]);
+        assert_eq!(a.difference(&b).ranges(), &[1..3, 9..11]);
+        assert_eq!(b.difference(&a).ranges(), &[5..8, 12..15]);
+    }
+
+    #[test_case(2..9, 3..8, true)]
+    #[test_case(3..8, 2..9, false)]
+    #[test_case(2..5, 2..5, true)]
+    #[test_case(2..5, 5..6, false)]
+    fn test_is_superset(a: Range<u32>, b: Range<u32>, expected: bool) {
+        assert_eq!(
+            IntervalSet::from(a).is_superset(&IntervalSet::from(b)),
+            expected
+        );
+    }
+
+    #[test_case(4, true)]
+    #[test_case(5, false)]
+    #[test_case(8, true)]
+    #[test_case(0, false)]
+    fn test_contains(value: u32, expected: bool) {
+        let set = IntervalSet::from_ranges([1..5, 8..12]);
+        assert_eq!(set.contains(value), expected);
+    }
+}
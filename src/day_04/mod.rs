@@ -1,60 +1,102 @@
 use std::ops::Range;
 
-pub fn count_containing_pairs(input: &str) -> usize {
-    let pairs = input
-        .lines()
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .map(parse_to_ranges)
-        .filter(ranges_are_contained)
-        .count();
+mod interval_set;
+pub use interval_set::IntervalSet;
+
+pub fn count_containing_pairs(input: &str) -> Result<usize, String> {
+    let assignments = parse_assignment_lines(input)?;
+    return Ok(assignments
+        .iter()
+        .filter(|ranges| ranges_are_contained(ranges))
+        .count());
+}
+
+pub fn count_overlapping_pairs(input: &str) -> Result<usize, String> {
+    let assignments = parse_assignment_lines(input)?;
+    return Ok(assignments
+        .iter()
+        .filter(|ranges| ranges_overlap(ranges))
+        .count());
+}
+
+pub fn find_uncovered_sections(input: &str) -> Result<Vec<Range<u32>>, String> {
+    return Ok(build_coverage(input)?.gaps());
+}
+
+pub fn count_elves_covering(input: &str, section: u32) -> Result<usize, String> {
+    let assignments = parse_assignment_lines(input)?;
+    return Ok(assignments
+        .iter()
+        .flatten()
+        .filter(|a| a.contains(section))
+        .count());
+}
 
-    return pairs;
+pub fn build_coverage(input: &str) -> Result<IntervalSet, String> {
+    let assignments = parse_assignment_lines(input)?;
+    return Ok(assignments
+        .iter()
+        .flatten()
+        .fold(IntervalSet::new(), |acc, a| acc.union(a)));
 }
 
-pub fn count_overlapping_pairs(input: &str) -> usize {
+fn parse_assignment_lines(input: &str) -> Result<Vec<Vec<IntervalSet>>, String> {
     return input
         .lines()
         .map(str::trim)
         .filter(|s| !s.is_empty())
         .map(parse_to_ranges)
-        .filter(ranges_overlap)
-        .count();
+        .collect();
 }
 
-fn parse_to_ranges(line: &str) -> Vec<Range<u32>> {
-    let x = line.split(',').map(parse_range).collect();
-    println!("Pair: {:?}", x);
-    return x;
+fn parse_to_ranges(line: &str) -> Result<Vec<IntervalSet>, String> {
+    return line
+        .split(',')
+        .map(|value| Ok(IntervalSet::from(parse_range(value)?)))
+        .collect();
 }
 
-fn ranges_are_contained(ranges: &Vec<Range<u32>>) -> bool {
-    let a = ranges[0].to_owned();
-    let b = ranges[1].to_owned();
-    if a.start <= b.start && a.end >= b.end {
-        return true;
-    }
-    if b.start <= a.start && b.end >= a.end {
-        return true;
+// True when any assignment on the line fully contains another one.
+fn ranges_are_contained(sets: &[IntervalSet]) -> bool {
+    for i in 0..sets.len() {
+        for j in 0..sets.len() {
+            if i != j && sets[i].is_superset(&sets[j]) {
+                return true;
+            }
+        }
     }
     return false;
 }
 
-fn ranges_overlap(ranges: &Vec<Range<u32>>) -> bool {
-    let a = ranges[0].to_owned();
-    let b = ranges[1].to_owned();
-    if a.contains(&b.start) || b.contains(&a.start) {
-        return true;
+// True when any two assignments on the line share a section.
+fn ranges_overlap(sets: &[IntervalSet]) -> bool {
+    for i in 0..sets.len() {
+        for j in i + 1..sets.len() {
+            if sets[i].overlaps(&sets[j]) {
+                return true;
+            }
+        }
     }
     return false;
 }
 
-fn parse_range(value: &str) -> Range<u32> {
-    let vals: Vec<&str> = value.split('-').collect();
-    return Range {
-        start: vals[0].parse().expect("Value not parsable"),
-        end: vals[1].parse::<u32>().expect("Value not parsable") + 1,
+fn parse_range(value: &str) -> Result<Range<u32>, String> {
+    let (start, end) = value
+        .trim()
+        .split_once('-')
+        .ok_or(format!("Assignment is not a range: {:?}", value))?;
+    let parse_section = |s: &str| -> Result<u32, String> {
+        return s
+            .trim()
+            .parse()
+            .map_err(|_| format!("Section not parsable: {:?}", s));
     };
+    let start = parse_section(start)?;
+    let end = parse_section(end)?;
+    if end < start || end == u32::MAX {
+        return Err(format!("Invalid assignment: {:?}", value));
+    }
+    return Ok(start..end + 1);
 }
 
 #[cfg(test)]
@@ -73,7 +115,7 @@ mod tests {
             2-6,4-8
         "#;
         let count = count_containing_pairs(input);
-        assert_eq!(count, 2);
+        assert_eq!(count, Ok(2));
     }
 
     #[test]
@@ -87,12 +129,38 @@ mod tests {
             2-6,4-8
         "#;
         let count = count_overlapping_pairs(input);
-        assert_eq!(count, 4);
+        assert_eq!(count, Ok(4));
     }
 
     #[test_case(5..8, 7..10, true)]
+    #[test_case(5..7, 7..10, false)]
     fn test_overlap(a: Range<u32>, b: Range<u32>, expected_overlap: bool) {
-        let overlaps = ranges_overlap(&vec![a, b]);
+        let overlaps = ranges_overlap(&[a.into(), b.into()]);
         assert_eq!(overlaps, expected_overlap);
     }
+
+    #[test_case("2-4,6-8,3-3", true)]
+    #[test_case("2-4,6-8,9-9", false)]
+    fn test_containment_with_many_assignments(line: &str, expected: bool) {
+        let ranges = parse_to_ranges(line).unwrap();
+        assert_eq!(ranges_are_contained(&ranges), expected);
+    }
+
+    #[test]
+    fn test_uncovered_and_shared_sections() {
+        let input = r#"
+            2-4,6-8,12-12
+            2-3,4-5
+        "#;
+        assert_eq!(find_uncovered_sections(input), Ok(vec![9..12]));
+        assert_eq!(count_elves_covering(input, 4), Ok(2));
+        assert_eq!(count_elves_covering(input, 10), Ok(0));
+    }
+
+    #[test_case("2-x")]
+    #[test_case("24")]
+    #[test_case("5-3")]
+    fn test_malformed_range(value: &str) {
+        assert!(parse_range(value).is_err());
+    }
 }
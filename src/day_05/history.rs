@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use super::{apply_move, parse_to_moves, CrateMover, Move};
+
+// Steps a crane through a move list while logging what each move lifted, so
+// any move can be undone and the run can be replayed from any point.
+pub struct CraneHistory<'a> {
+    mover: &'a dyn CrateMover,
+    start: Vec<Vec<char>>,
+    stacks: Vec<Vec<char>>,
+    moves: Vec<Move>,
+    log: Vec<LoggedMove>,
+}
+
+// The crates as they sat on the source before the move, bottom first.
+struct LoggedMove {
+    lifted: Vec<char>,
+}
+
+impl<'a> CraneHistory<'a> {
+    pub fn new(
+        mover: &'a dyn CrateMover,
+        move_text: &str,
+        stacks: Vec<Vec<char>>,
+    ) -> Result<Self, String> {
+        return Ok(Self {
+            mover,
+            start: stacks.clone(),
+            stacks,
+            moves: parse_to_moves(move_text)?,
+            log: Vec::new(),
+        });
+    }
+
+    pub fn stacks(&self) -> &[Vec<char>] {
+        return &self.stacks;
+    }
+
+    // Number of moves currently applied.
+    pub fn position(&self) -> usize {
+        return self.log.len();
+    }
+
+    pub fn move_count(&self) -> usize {
+        return self.moves.len();
+    }
+
+    // Applies the next move, returning false once every move is applied.
+    pub fn step_forward(&mut self) -> Result<bool, String> {
+        let Some(m) = self.moves.get(self.log.len()) else {
+            return Ok(false);
+        };
+        let step = self.log.len() + 1;
+        let source = self.stacks.get(m.source).map_or(&[][..], |s| &s[..]);
+        let lifted = source[source.len().saturating_sub(m.count)..].to_vec();
+        apply_move(self.mover, &mut self.stacks, m).map_err(|e| format!("Move {}: {}", step, e))?;
+        self.log.push(LoggedMove { lifted });
+        return Ok(true);
+    }
+
+    // Takes back the last applied move, returning false at the start. A
+    // move that can't be undone stays applied and logged.
+    pub fn undo(&mut self) -> Result<bool, String> {
+        let Some(entry) = self.log.pop() else {
+            return Ok(false);
+        };
+        let m = &self.moves[self.log.len()];
+        let target = &mut self.stacks[m.target];
+        if target.len() < m.count {
+            self.log.push(entry);
+            return Err(format!("Cannot undo {}: target is short", m));
+        }
+        target.truncate(target.len() - m.count);
+        self.stacks[m.source].extend(entry.lifted);
+        return Ok(true);
+    }
+
+    // Undoes or replays moves until exactly `position` moves are applied.
+    pub fn seek(&mut self, position: usize) -> Result<(), String> {
+        if position > self.moves.len() {
+            return Err(format!(
+                "Only {} moves, cannot seek to {}",
+                self.moves.len(),
+                position
+            ));
+        }
+        while self.position() > position {
+            self.undo()?;
+        }
+        while self.position() < position {
+            self.step_forward()?;
+        }
+        return Ok(());
+    }
+
+    // Every crate that was there at the start is still there, and undoing
+    // everything gets back the exact starting arrangement.
+    pub fn verify(&self) -> Result<(), String> {
+        if crate_counts(&self.start) != crate_counts(&self.stacks) {
+            return Err(format!(
+                "Crates changed after {} moves: {:?} became {:?}",
+                self.position(),
+                crate_counts(&self.start),
+                crate_counts(&self.stacks)
+            ));
+        }
+        let rebuilt = undo_moves(self.mover, &self.stacks, &self.moves[..self.position()])?;
+        if rebuilt != self.start {
+            return Err("Undoing every move does not restore the start".to_string());
+        }
+        return Ok(());
+    }
+}
+
+// Runs the moves with both cranes side by side and returns the first move
+// after which their stacks differ.
+pub fn find_first_divergence(
+    a: &dyn CrateMover,
+    b: &dyn CrateMover,
+    move_text: &str,
+    stacks: Vec<Vec<char>>,
+) -> Result<Option<usize>, String> {
+    let mut history_a = CraneHistory::new(a, move_text, stacks.clone())?;
+    let mut history_b = CraneHistory::new(b, move_text, stacks)?;
+    while history_a.step_forward()? {
+        history_b.step_forward()?;
+        if history_a.stacks() != history_b.stacks() {
+            return Ok(Some(history_a.position()));
+        }
+    }
+    return Ok(None);
+}
+
+// Works backwards from a final arrangement to the one the moves started from.
+pub fn rebuild_start(
+    mover: &dyn CrateMover,
+    final_stacks: &[Vec<char>],
+    move_text: &str,
+) -> Result<Vec<Vec<char>>, String> {
+    return undo_moves(mover, final_stacks, &parse_to_moves(move_text)?);
+}
+
+fn undo_moves(
+    mover: &dyn CrateMover,
+    final_stacks: &[Vec<char>],
+    moves: &[Move],
+) -> Result<Vec<Vec<char>>, String> {
+    let mut stacks = final_stacks.to_vec();
+    for (i, m) in moves.iter().enumerate().rev() {
+        if m.source >= stacks.len() || m.target >= stacks.len() {
+            return Err(format!("Move {}: no such stack for {}", i + 1, m));
+        }
+        let target = &mut stacks[m.target];
+        if target.len() < m.count {
+            return Err(format!(
+                "Move {}: stack {} only has {} crates to take back",
+                i + 1,
+                m.target + 1,
+                target.len()
+            ));
+        }
+        let placed = target.split_off(target.len() - m.count);
+        stacks[m.source].extend(mover.undo_pick_up(placed));
+    }
+    return Ok(stacks);
+}
+
+fn crate_counts(stacks: &[Vec<char>]) -> HashMap<char, usize> {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in stacks.iter().flatten() {
+        *counts.entry(*c).or_insert(0) += 1;
+    }
+    return counts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CapacityLimitedMover, CrateMover9000, CrateMover9001};
+    use super::*;
+
+    const SAMPLE_MOVES: &str = r#"
+        move 1 from 2 to 1
+        move 3 from 1 to 3
+        move 2 from 2 to 1
+        move 1 from 1 to 2
+    "#;
+
+    fn sample_stacks() -> Vec<Vec<char>> {
+        return vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+    }
+
+    #[test]
+    fn test_undo_and_replay() {
+        let mut history =
+            CraneHistory::new(&CrateMover9000, SAMPLE_MOVES, sample_stacks()).unwrap();
+        history.seek(4).unwrap();
+        assert_eq!(
+            history.stacks(),
+            &[vec!['C'], vec!['M'], vec!['P', 'D', 'N', 'Z']]
+        );
+        history.verify().unwrap();
+
+        history.seek(1).unwrap();
+        assert_eq!(
+            history.stacks(),
+            &[vec!['Z', 'N', 'D'], vec!['M', 'C'], vec!['P']]
+        );
+        assert!(history.undo().unwrap());
+        assert!(!history.undo().unwrap());
+        assert_eq!(history.stacks(), &sample_stacks()[..]);
+        assert!(history.seek(5).is_err());
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let divergence = find_first_divergence(
+            &CrateMover9000,
+            &CrateMover9001,
+            SAMPLE_MOVES,
+            sample_stacks(),
+        );
+        assert_eq!(divergence, Ok(Some(2)));
+        let same = find_first_divergence(
+            &CrateMover9000,
+            &CapacityLimitedMover { capacity: 1 },
+            SAMPLE_MOVES,
+            sample_stacks(),
+        );
+        assert_eq!(same, Ok(None));
+    }
+
+    #[test]
+    fn test_rebuild_start_for_each_crane() {
+        let movers: Vec<Box<dyn CrateMover>> = vec![
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(CapacityLimitedMover { capacity: 2 }),
+        ];
+        for mover in movers {
+            let mut history =
+                CraneHistory::new(mover.as_ref(), SAMPLE_MOVES, sample_stacks()).unwrap();
+            history.seek(history.move_count()).unwrap();
+            let rebuilt = rebuild_start(mover.as_ref(), history.stacks(), SAMPLE_MOVES);
+            assert_eq!(rebuilt, Ok(sample_stacks()), "{}", mover.name());
+        }
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_the_move() {
+        let mut history =
+            CraneHistory::new(&CrateMover9000, SAMPLE_MOVES, sample_stacks()).unwrap();
+        history.seek(2).unwrap();
+        let taken = history.stacks[2].split_off(1);
+        assert_eq!(
+            history.undo(),
+            Err("Cannot undo move 3 from 1 to 3: target is short".to_string())
+        );
+        assert_eq!(history.position(), 2);
+        history.stacks[2].extend(taken);
+        assert!(history.undo().unwrap());
+        assert_eq!(history.position(), 1);
+        assert_eq!(
+            history.stacks(),
+            &[vec!['Z', 'N', 'D'], vec!['M', 'C'], vec!['P']]
+        );
+    }
+
+    #[test]
+    fn test_verify_catches_lost_crates() {
+        let mut history =
+            CraneHistory::new(&CrateMover9001, SAMPLE_MOVES, sample_stacks()).unwrap();
+        history.seek(2).unwrap();
+        history.stacks[1].pop();
+        assert!(history.verify().unwrap_err().starts_with("Crates changed"));
+    }
+}
@@ -1,44 +1,217 @@
-use std::vec;
+use std::{fmt, vec};
 
 use regex::{self, Regex};
 
-pub fn calculate_crane_moves(move_text: &str, mut stacks: Vec<Vec<char>>) -> String {
-    let moves = parse_to_moves(move_text);
+mod history;
+pub use history::{find_first_divergence, rebuild_start, CraneHistory};
+
+pub fn calculate_crane_moves(move_text: &str, stacks: Vec<Vec<char>>) -> Result<String, String> {
+    return run_crane(&CrateMover9000, move_text, stacks);
+}
+
+pub fn calculate_crate_mover_9001(
+    move_text: &str,
+    stacks: Vec<Vec<char>>,
+) -> Result<String, String> {
+    return run_crane(&CrateMover9001, move_text, stacks);
+}
+
+pub fn run_crane(
+    mover: &dyn CrateMover,
+    move_text: &str,
+    stacks: Vec<Vec<char>>,
+) -> Result<String, String> {
+    let moves = parse_to_moves(move_text)?;
 
     log_stacks(&stacks);
-    println!("Processing {:?} moves", moves.len());
+    println!("Processing {:?} moves with {}", moves.len(), mover.name());
 
-    let mut move_count = 0;
-    for m in moves {
-        move_count += 1;
-        if stacks[m.source].len() < m.count {
-            panic!("Stack is empty for move: {:?} {:?}", move_count, m);
-        }
-        for _i in 0..m.count {
-            let ch = stacks[m.source].pop().unwrap();
-            stacks[m.target].push(ch);
+    let stacks = apply_moves(mover, &moves, stacks, |_, _, _| {})?;
+
+    log_stacks(&stacks);
+    return Ok(get_stack_tops(stacks));
+}
+
+// Draws the starting diagram, then the diagram after every `every` moves and
+// after the last one.
+pub fn animate_crane(
+    mover: &dyn CrateMover,
+    move_text: &str,
+    stacks: Vec<Vec<char>>,
+    every: usize,
+) -> Result<String, String> {
+    let moves = parse_to_moves(move_text)?;
+    let every = every.max(1);
+    let mut frames = vec![format!(
+        "Start ({}):\n{}",
+        mover.name(),
+        render_stacks(&stacks)
+    )];
+    apply_moves(mover, &moves, stacks, |step, m, stacks| {
+        if step % every == 0 || step == moves.len() {
+            frames.push(format!(
+                "After {} ({}):\n{}",
+                step,
+                m,
+                render_stacks(stacks)
+            ));
         }
+    })?;
+    return Ok(frames.join("\n"));
+}
+
+// Draws the stacks the way the puzzle does, with the numbered footer.
+pub fn render_stacks(stacks: &[Vec<char>]) -> String {
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut lines: Vec<String> = Vec::with_capacity(height + 1);
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|s| match s.get(level) {
+                Some(c) => format!("[{}]", c),
+                None => "   ".to_string(),
+            })
+            .collect();
+        lines.push(cells.join(" "));
     }
+    let footer: Vec<String> = (1..=stacks.len()).map(|i| format!("{:^3}", i)).collect();
+    lines.push(footer.join(" "));
+    return lines.join("\n") + "\n";
+}
 
-    log_stacks(&stacks);
-    return get_stack_tops(stacks);
+// Shared by every crane model. `on_step` sees the stacks after each move,
+// numbered from 1.
+fn apply_moves<F: FnMut(usize, &Move, &[Vec<char>])>(
+    mover: &dyn CrateMover,
+    moves: &[Move],
+    mut stacks: Vec<Vec<char>>,
+    mut on_step: F,
+) -> Result<Vec<Vec<char>>, String> {
+    for (i, m) in moves.iter().enumerate() {
+        apply_move(mover, &mut stacks, m).map_err(|e| format!("Move {}: {}", i + 1, e))?;
+        on_step(i + 1, m, &stacks);
+    }
+    return Ok(stacks);
 }
 
-pub fn calculate_crate_mover_9001(move_text: &str, mut stacks: Vec<Vec<char>>) -> String {
-    let moves = parse_to_moves(move_text);
+// The only thing that differs between crane models is how a pick-up of
+// `count` crates comes off the top of a stack. The returned crates are in
+// the order they end up on the target, bottom first. The stack is already
+// known to hold at least `count` crates.
+//
+// `undo_pick_up` is the inverse: given crates as they were placed, it
+// returns them in the order they sat on the source stack.
+pub trait CrateMover {
+    fn name(&self) -> String;
+    fn pick_up(&self, stack: &mut Vec<char>, count: usize) -> Result<Vec<char>, String>;
+    fn undo_pick_up(&self, crates: Vec<char>) -> Vec<char>;
+}
+
+// Moves one crate at a time, so a pick-up lands reversed.
+pub struct CrateMover9000;
 
-    for m in moves {
-        let cut_index = stacks[m.source].len() - m.count;
-        let mut pick_up_crates = stacks[m.source].split_off(cut_index);
-        stacks[m.target].append(&mut pick_up_crates);
+impl CrateMover for CrateMover9000 {
+    fn name(&self) -> String {
+        return "CrateMover 9000".to_string();
     }
 
-    return get_stack_tops(stacks);
+    fn pick_up(&self, stack: &mut Vec<char>, count: usize) -> Result<Vec<char>, String> {
+        let mut crates = stack.split_off(stack.len() - count);
+        crates.reverse();
+        return Ok(crates);
+    }
+
+    fn undo_pick_up(&self, mut crates: Vec<char>) -> Vec<char> {
+        crates.reverse();
+        return crates;
+    }
 }
 
-fn parse_to_moves(move_text: &str) -> Vec<Move> {
-    let moves: Vec<Move> = move_text.trim().lines().map(parse_to_move).collect();
-    return moves;
+// Moves the whole pick-up at once, keeping its order.
+pub struct CrateMover9001;
+
+impl CrateMover for CrateMover9001 {
+    fn name(&self) -> String {
+        return "CrateMover 9001".to_string();
+    }
+
+    fn pick_up(&self, stack: &mut Vec<char>, count: usize) -> Result<Vec<char>, String> {
+        return Ok(stack.split_off(stack.len() - count));
+    }
+
+    fn undo_pick_up(&self, crates: Vec<char>) -> Vec<char> {
+        return crates;
+    }
+}
+
+// Keeps order like the 9001 but can only lift `capacity` crates per trip.
+pub struct CapacityLimitedMover {
+    pub capacity: usize,
+}
+
+impl CrateMover for CapacityLimitedMover {
+    fn name(&self) -> String {
+        return format!("capacity {} crane", self.capacity);
+    }
+
+    fn pick_up(&self, stack: &mut Vec<char>, count: usize) -> Result<Vec<char>, String> {
+        if self.capacity == 0 && count > 0 {
+            return Err(format!("{} cannot lift anything", self.name()));
+        }
+        let mut crates: Vec<char> = Vec::with_capacity(count);
+        let mut remaining = count;
+        while remaining > 0 {
+            let trip = remaining.min(self.capacity);
+            crates.append(&mut stack.split_off(stack.len() - trip));
+            remaining -= trip;
+        }
+        return Ok(crates);
+    }
+
+    fn undo_pick_up(&self, crates: Vec<char>) -> Vec<char> {
+        if self.capacity == 0 {
+            return crates;
+        }
+        return crates
+            .chunks(self.capacity)
+            .rev()
+            .flatten()
+            .cloned()
+            .collect();
+    }
+}
+
+fn apply_move(mover: &dyn CrateMover, stacks: &mut [Vec<char>], m: &Move) -> Result<(), String> {
+    if m.source >= stacks.len() || m.target >= stacks.len() {
+        return Err(format!("No such stack for {}", m));
+    }
+    if stacks[m.source].len() < m.count {
+        return Err(format!(
+            "Stack {} only has {} crates for {}",
+            m.source + 1,
+            stacks[m.source].len(),
+            m
+        ));
+    }
+    let mut crates = mover.pick_up(&mut stacks[m.source], m.count)?;
+    stacks[m.target].append(&mut crates);
+    return Ok(());
+}
+
+fn parse_to_moves(move_text: &str) -> Result<Vec<Move>, String> {
+    let move_regex = Regex::new(r".*move (\d+) from (\d+) to (\d+)").unwrap();
+    return move_text
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            parse_to_move(&move_regex, line).ok_or(format!(
+                "Line {} is not a move: {:?}",
+                i + 1,
+                line.trim()
+            ))
+        })
+        .collect();
 }
 
 fn get_stack_tops(stacks: Vec<Vec<char>>) -> String {
@@ -47,12 +220,8 @@ fn get_stack_tops(stacks: Vec<Vec<char>>) -> String {
     return tops;
 }
 
-fn log_stacks(stacks: &Vec<Vec<char>>) {
-    let mut s_index = 0;
-    for s in stacks.clone() {
-        println!("{:?}: {:?}", s_index, s);
-        s_index += 1;
-    }
+fn log_stacks(stacks: &[Vec<char>]) {
+    println!("{}", render_stacks(stacks));
 }
 
 pub fn build_stacks() -> Vec<Vec<char>> {
@@ -62,28 +231,41 @@ pub fn build_stacks() -> Vec<Vec<char>> {
     return ordered.iter().map(|s| s.chars().rev().collect()).collect();
 }
 
-fn parse_to_move(line: &str) -> Move {
-    let move_regex = Regex::new(r".*move (\d+) from (\d+) to (\d+)").unwrap();
-    let captures = move_regex.captures(line).unwrap();
+fn parse_to_move(move_regex: &Regex, line: &str) -> Option<Move> {
+    let captures = move_regex.captures(line)?;
+    let parse = |i: usize| captures.get(i)?.as_str().parse::<usize>().ok();
     let m = Move {
-        count: captures.get(1).unwrap().as_str().parse::<usize>().unwrap(),
-        source: captures.get(2).unwrap().as_str().parse::<usize>().unwrap() - 1,
-        target: captures.get(3).unwrap().as_str().parse::<usize>().unwrap() - 1,
+        count: parse(1)?,
+        source: parse(2)?.checked_sub(1)?,
+        target: parse(3)?.checked_sub(1)?,
     };
     // println!("Parsed move: {:?}", m);
-    return m;
+    return Some(m);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Move {
     source: usize,
     target: usize,
     count: usize,
 }
 
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count,
+            self.source + 1,
+            self.target + 1
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     #[test]
     fn test_sample_input() {
@@ -95,7 +277,7 @@ mod tests {
             move 1 from 1 to 2
         "#;
         let resulting_top = calculate_crane_moves(input.trim(), stacks);
-        assert_eq!(resulting_top, "CMZ");
+        assert_eq!(resulting_top, Ok("CMZ".to_string()));
     }
 
     #[test]
@@ -108,6 +290,100 @@ mod tests {
             move 1 from 1 to 2
         "#;
         let resulting_top = calculate_crate_mover_9001(input.trim(), stacks);
-        assert_eq!(resulting_top, "MCD");
+        assert_eq!(resulting_top, Ok("MCD".to_string()));
+    }
+
+    #[test]
+    fn test_capacity_limited_crane() {
+        let input = r#"
+            move 1 from 2 to 1
+            move 3 from 1 to 3
+            move 2 from 2 to 1
+            move 1 from 1 to 2
+        "#;
+        let sample_stacks = || vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+        let one_at_a_time = run_crane(
+            &CapacityLimitedMover { capacity: 1 },
+            input,
+            sample_stacks(),
+        );
+        assert_eq!(one_at_a_time, Ok("CMZ".to_string()));
+        let unlimited = run_crane(
+            &CapacityLimitedMover { capacity: 3 },
+            input,
+            sample_stacks(),
+        );
+        assert_eq!(unlimited, Ok("MCD".to_string()));
+
+        let mut stack = vec!['A', 'B', 'C', 'D', 'E'];
+        let crates = CapacityLimitedMover { capacity: 2 }.pick_up(&mut stack, 5);
+        assert_eq!(crates, Ok(vec!['D', 'E', 'B', 'C', 'A']));
+        let restored = CapacityLimitedMover { capacity: 2 }.undo_pick_up(crates.unwrap());
+        assert_eq!(restored, vec!['A', 'B', 'C', 'D', 'E']);
+
+        let stuck = run_crane(
+            &CapacityLimitedMover { capacity: 0 },
+            input,
+            sample_stacks(),
+        );
+        assert_eq!(
+            stuck,
+            Err("Move 1: capacity 0 crane cannot lift anything".to_string())
+        );
+    }
+
+    #[test_case(
+        "move 4 from 2 to 1",
+        "Move 1: Stack 2 only has 3 crates for move 4 from 2 to 1"
+    )]
+    #[test_case("move 1 from 4 to 1", "Move 1: No such stack for move 1 from 4 to 1")]
+    #[test_case("move 1 from 0 to 1", "Line 1 is not a move")]
+    #[test_case("move 1 from 2 to 1\nmove one from 2 to 1", "Line 2 is not a move")]
+    fn test_errors_are_reported_for_every_crane(input: &str, expected_error: &str) {
+        let movers: Vec<Box<dyn CrateMover>> = vec![
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(CapacityLimitedMover { capacity: 2 }),
+        ];
+        for mover in movers {
+            let stacks = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+            let err = run_crane(mover.as_ref(), input, stacks).unwrap_err();
+            assert!(err.starts_with(expected_error), "{}: {}", mover.name(), err);
+        }
+    }
+
+    #[test]
+    fn test_render_stacks() {
+        let stacks = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+        let expected = ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "];
+        assert_eq!(render_stacks(&stacks), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn test_animate_every_two_moves() {
+        let stacks = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+        let input = r#"
+            move 1 from 2 to 1
+            move 3 from 1 to 3
+            move 2 from 2 to 1
+        "#;
+        let animation = animate_crane(&CrateMover9000, input, stacks, 2).unwrap();
+        let titles: Vec<&str> = animation.lines().filter(|l| l.ends_with("):")).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Start (CrateMover 9000):",
+                "After 2 (move 3 from 1 to 3):",
+                "After 3 (move 2 from 2 to 1):",
+            ]
+        );
+        let expected_last = [
+            "        [Z]",
+            "        [N]",
+            "[M]     [D]",
+            "[C]     [P]",
+            " 1   2   3 ",
+        ];
+        assert!(animation.ends_with(&(expected_last.join("\n") + "\n")));
     }
 }
@@ -1,30 +1,103 @@
-use std::collections::HashSet;
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, Read},
+};
 
-pub fn calculate_first_marker(data_stream: &str) -> usize {
+pub fn calculate_first_marker(data_stream: &str) -> Option<usize> {
     return calculate_index_of_unique_run(data_stream, 4);
 }
 
-pub fn calculate_start_of_message_index(data_stream: &str) -> usize {
+pub fn calculate_start_of_message_index(data_stream: &str) -> Option<usize> {
     return calculate_index_of_unique_run(data_stream, 14);
 }
 
-pub fn calculate_index_of_unique_run(data_stream: &str, length: usize) -> usize {
-    for i in 0..data_stream.len() {
-        if i < length {
-            continue;
+// Runs and positions are counted in bytes, so the stream is expected to be
+// ASCII; a multi-byte character counts as several bytes. None when there is
+// no such run, which is always the case for a length of 0.
+pub fn calculate_index_of_unique_run(data_stream: &str, length: usize) -> Option<usize> {
+    return find_unique_run_ends(data_stream.bytes(), length)?.next();
+}
+
+// None for a length of 0.
+pub fn find_unique_run_ends<I: IntoIterator<Item = u8>>(
+    bytes: I,
+    length: usize,
+) -> Option<impl Iterator<Item = usize>> {
+    let mut detector = UniqueRunDetector::new(length)?;
+    return Some(bytes.into_iter().filter_map(move |b| {
+        if detector.push(b) {
+            Some(detector.position)
+        } else {
+            None
+        }
+    }));
+}
+
+// Lazily yields the end of every unique run, so callers can stop at the
+// first marker without reading the rest of the stream. None for a length
+// of 0.
+pub fn find_unique_run_ends_in_reader<R: Read>(
+    reader: R,
+    length: usize,
+) -> Option<impl Iterator<Item = io::Result<usize>>> {
+    let mut detector = UniqueRunDetector::new(length)?;
+    return Some(BufReader::new(reader).bytes().filter_map(move |b| match b {
+        Ok(b) if detector.push(b) => Some(Ok(detector.position)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }));
+}
+
+// Tracks how many times each byte appears in the current window, so every
+// push is O(1) no matter how long the window or the stream is.
+struct UniqueRunDetector {
+    length: usize,
+    counts: [usize; 256],
+    window: VecDeque<u8>,
+    duplicates: usize,
+    position: usize,
+}
+
+impl UniqueRunDetector {
+    // None for a length of 0, which no window can fill.
+    fn new(length: usize) -> Option<Self> {
+        if length == 0 {
+            return None;
+        }
+        return Some(Self {
+            length,
+            counts: [0; 256],
+            window: VecDeque::with_capacity(length + 1),
+            duplicates: 0,
+            position: 0,
+        });
+    }
+
+    // Returns true when the window ending at the pushed byte is all unique.
+    fn push(&mut self, byte: u8) -> bool {
+        self.position += 1;
+        self.window.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.duplicates += 1;
         }
-        let history_slice = &data_stream[(i - length)..i];
-        let x: HashSet<char> = HashSet::from_iter(history_slice.chars());
-        if x.len() == length {
-            return i;
+
+        if self.window.len() > self.length {
+            let dropped = self.window.pop_front().unwrap() as usize;
+            self.counts[dropped] -= 1;
+            if self.counts[dropped] == 1 {
+                self.duplicates -= 1;
+            }
         }
+
+        return self.window.len() == self.length && self.duplicates == 0;
     }
-    return 0;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Cursor;
     use test_case::test_case;
 
     #[test_case("bvwbjplbgvbhsrlpgdmjqwftvncz", 5)]
@@ -33,7 +106,7 @@ mod tests {
     #[test_case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11)]
     fn test_calculate_first_marker(data_stream: &str, expected_pos: usize) {
         let index = calculate_first_marker(data_stream);
-        assert_eq!(index, expected_pos);
+        assert_eq!(index, Some(expected_pos));
     }
 
     #[test_case("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 19)]
@@ -43,6 +116,44 @@ mod tests {
     #[test_case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 26)]
     fn test_calculate_start_of_message_index(data_stream: &str, expected_pos: usize) {
         let index = calculate_start_of_message_index(data_stream);
-        assert_eq!(index, expected_pos);
+        assert_eq!(index, Some(expected_pos));
+    }
+
+    #[test_case("", 4)]
+    #[test_case("abc", 4)]
+    #[test_case("aaaaaaaa", 2)]
+    #[test_case("abcd", 0)]
+    fn test_no_marker_found(data_stream: &str, length: usize) {
+        assert_eq!(calculate_index_of_unique_run(data_stream, length), None);
+    }
+
+    // Each é is two bytes, and the run is counted over bytes.
+    #[test]
+    fn test_non_ascii_does_not_panic() {
+        assert_eq!(calculate_index_of_unique_run("ééab", 4), Some(6));
+    }
+
+    #[test]
+    fn test_all_marker_positions() {
+        let ends: Vec<usize> = find_unique_run_ends("aabcab".bytes(), 3).unwrap().collect();
+        assert_eq!(ends, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_reader_over_large_stream() {
+        let mut data = "abab".repeat(1_000_000).into_bytes();
+        data.extend_from_slice(b"cdab");
+        let first = find_unique_run_ends_in_reader(Cursor::new(&data), 4)
+            .unwrap()
+            .next()
+            .transpose()
+            .unwrap();
+        assert_eq!(first, Some(4_000_002));
+        let all: Vec<usize> = find_unique_run_ends_in_reader(Cursor::new(&data), 4)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(all, vec![4_000_002, 4_000_003, 4_000_004]);
+        assert!(find_unique_run_ends_in_reader(Cursor::new(&data), 0).is_none());
     }
 }
@@ -1,8 +1,12 @@
+use std::fmt;
+
+use crate::grid::Grid;
+
 pub fn count_visible_trees(input: &str) -> u32 {
     let forest = parse_string_to_forest(input);
     let mut tree_count = 0;
-    for row in 0..forest.len() {
-        for col in 0..forest[row].len() {
+    for row in 0..forest.height() {
+        for col in 0..forest.width() {
             if is_tree_visible(row, col, &forest) {
                 tree_count += 1;
             }
@@ -12,21 +16,84 @@ pub fn count_visible_trees(input: &str) -> u32 {
 }
 
 pub fn calc_max_senic_score(input: &str) -> u32 {
+    return find_best_tree(input).score;
+}
+
+pub fn find_best_tree(input: &str) -> BestTree {
     let forest = parse_string_to_forest(input);
-    let mut max = 0;
-    for row in 0..forest.len() {
-        for col in 0..forest[row].len() {
-            let score = senic_score(row, col, &forest);
-            if score > max {
-                max = score;
-            }
+    let scores = compute_senic_scores(&forest);
+    let mut best = BestTree {
+        row: 0,
+        col: 0,
+        score: 0,
+    };
+    for (point, score) in scores.iter() {
+        if *score > best.score {
+            let (col, row) = point.to_unsigned().unwrap();
+            best = BestTree {
+                row,
+                col,
+                score: *score,
+            };
         }
     }
-    return max;
+    return best;
 }
 
-fn senic_score(row: usize, col: usize, forest: &Vec<Vec<u8>>) -> u32 {
-    let height = forest[row][col];
+pub fn render_visibility_mask(input: &str) -> String {
+    let forest = parse_string_to_forest(input);
+    let mut mask = Grid::new(forest.width(), forest.height(), '.');
+    for point in forest.points() {
+        let (col, row) = point.to_unsigned().unwrap();
+        if is_tree_visible(row, col, &forest) {
+            mask[point] = '#';
+        }
+    }
+    return mask.to_string();
+}
+
+// Plain (P2) PGM, scores scaled so the best tree is white.
+pub fn render_senic_score_pgm(input: &str) -> String {
+    let forest = parse_string_to_forest(input);
+    let scores = compute_senic_scores(&forest);
+    let max_score = scores.iter().map(|(_, s)| *s).max().unwrap_or(0).max(1) as u64;
+
+    let mut rendered = format!("P2\n{} {}\n255\n", scores.width(), scores.height());
+    for row in scores.rows() {
+        let line: Vec<String> = row
+            .iter()
+            .map(|s| (*s as u64 * 255 / max_score).to_string())
+            .collect();
+        rendered.push_str(&line.join(" "));
+        rendered.push('\n');
+    }
+    return rendered;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BestTree {
+    pub row: usize,
+    pub col: usize,
+    pub score: u32,
+}
+
+impl fmt::Display for BestTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at row {}, col {}", self.score, self.row, self.col)
+    }
+}
+
+fn compute_senic_scores(forest: &Grid<u8>) -> Grid<u32> {
+    let mut scores = Grid::new(forest.width(), forest.height(), 0);
+    for point in forest.points() {
+        let (col, row) = point.to_unsigned().unwrap();
+        scores[point] = senic_score(row, col, forest);
+    }
+    return scores;
+}
+
+fn senic_score(row: usize, col: usize, forest: &Grid<u8>) -> u32 {
+    let height = forest.row(row)[col];
     let calc_viewing_distance = |run: &Vec<u8>| -> usize {
         for i in 0..run.len() {
             if run[i] >= height {
@@ -35,45 +102,39 @@ fn senic_score(row: usize, col: usize, forest: &Vec<Vec<u8>>) -> u32 {
         }
         return run.len();
     };
-    let score_to_left = calc_viewing_distance(&forest[row][..col].iter().rev().cloned().collect());
-    let score_to_right = calc_viewing_distance(&forest[row][col + 1..].to_vec());
+    let horiz_slice = forest.row(row);
+    let score_to_left = calc_viewing_distance(&horiz_slice[..col].iter().rev().cloned().collect());
+    let score_to_right = calc_viewing_distance(&horiz_slice[col + 1..].to_vec());
 
-    let vert_slice: Vec<u8> = forest.iter().map(|row| row[col]).collect();
+    let vert_slice: Vec<u8> = forest.column(col).cloned().collect();
     let score_to_top = calc_viewing_distance(&vert_slice[..row].iter().rev().cloned().collect());
     let score_to_bottom = calc_viewing_distance(&vert_slice[row + 1..].to_vec());
 
-    println!(
-        "{:?} {:?} {:?} {:?}",
-        score_to_left, score_to_right, score_to_top, score_to_bottom
-    );
     return (score_to_left * score_to_right * score_to_top * score_to_bottom) as u32;
 }
 
-fn is_tree_visible(row: usize, col: usize, forest: &Vec<Vec<u8>>) -> bool {
-    let height = forest[row][col];
+fn is_tree_visible(row: usize, col: usize, forest: &Grid<u8>) -> bool {
+    let height = forest.row(row)[col];
     let shorter = |val: &u8| (val.to_owned() < height);
 
-    let vis_from_left = forest[row][..col].into_iter().all(shorter);
-    let vis_from_right = forest[row][col + 1..].into_iter().all(shorter);
+    let horiz_slice = forest.row(row);
+    let vis_from_left = horiz_slice[..col].into_iter().all(shorter);
+    let vis_from_right = horiz_slice[col + 1..].into_iter().all(shorter);
 
-    let vert_slice: Vec<u8> = forest.iter().map(|row| row[col]).collect();
+    let vert_slice: Vec<u8> = forest.column(col).cloned().collect();
     let vis_from_top = vert_slice[..row].into_iter().all(shorter);
     let vis_from_bottom = vert_slice[row + 1..].into_iter().all(shorter);
 
     return vis_from_left || vis_from_right || vis_from_top || vis_from_bottom;
 }
 
-fn parse_string_to_forest(input: &str) -> Vec<Vec<u8>> {
-    let forest: Vec<Vec<u8>> = input.trim().lines().map(parse_string_to_tree_row).collect();
-    return forest;
-}
-
-fn parse_string_to_tree_row(row: &str) -> Vec<u8> {
-    return row
-        .trim()
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as u8)
-        .collect();
+fn parse_string_to_forest(input: &str) -> Grid<u8> {
+    return Grid::parse_with(input, |c| {
+        c.to_digit(10)
+            .map(|d| d as u8)
+            .ok_or(format!("Tree height not a digit: {:?}", c))
+    })
+    .unwrap();
 }
 
 #[cfg(test)]
@@ -130,4 +191,60 @@ mod tests {
         let score = senic_score(row, col, &forest);
         assert_eq!(score, expected_score);
     }
+
+    #[test]
+    fn test_find_best_tree() {
+        let input = r#"
+            30373
+            25512
+            65332
+            33549
+            35390
+        "#;
+        let best = find_best_tree(input);
+        assert_eq!(
+            best,
+            BestTree {
+                row: 3,
+                col: 2,
+                score: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_visibility_mask() {
+        let input = r#"
+            30373
+            25512
+            65332
+            33549
+            35390
+        "#;
+        let expected = r#"
+#####
+###.#
+##.##
+#.#.#
+#####
+        "#
+        .trim_start();
+        assert_eq!(render_visibility_mask(input), expected.trim_end().to_string() + "\n");
+    }
+
+    #[test]
+    fn test_render_senic_score_pgm() {
+        let input = r#"
+            30373
+            25512
+            65332
+            33549
+            35390
+        "#;
+        let pgm = render_senic_score_pgm(input);
+        let lines: Vec<&str> = pgm.lines().collect();
+        assert_eq!(lines[..3], ["P2", "5 5", "255"]);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[6], "0 31 255 95 0");
+    }
 }
@@ -0,0 +1,170 @@
+use std::{cmp::Ordering, fmt};
+
+// Just enough unsigned arbitrary-precision arithmetic for exact worry levels.
+// Little-endian base 2^32 limbs with no trailing zero limbs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn from_u64(value: u64) -> Self {
+        let mut big = Self {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        };
+        big.trim();
+        return big;
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        return match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0] as u64),
+            2 => Some(self.limbs[0] as u64 | (self.limbs[1] as u64) << 32),
+            _ => None,
+        };
+    }
+
+    // Number of bits needed to write the value, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        return match self.limbs.last() {
+            Some(top) => 32 * (self.limbs.len() as u64 - 1) + 32 - top.leading_zeros() as u64,
+            None => 0,
+        };
+    }
+
+    pub fn is_zero(&self) -> bool {
+        return self.limbs.is_empty();
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs: Vec<u32> = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let a = *self.limbs.get(i).unwrap_or(&0) as u64;
+            let b = *other.limbs.get(i).unwrap_or(&0) as u64;
+            let sum = a + b + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut big = Self { limbs };
+        big.trim();
+        return big;
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return Self { limbs: Vec::new() };
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let cur = limbs[i + j] as u64 + (*a as u64) * (*b as u64) + carry;
+                limbs[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut big = Self { limbs };
+        big.trim();
+        return big;
+    }
+
+    pub fn div_rem_u64(&self, divisor: u64) -> (BigUint, u64) {
+        assert!(divisor != 0, "Division by zero");
+        let mut quotient = vec![0u32; self.limbs.len()];
+        let mut rem = 0u128;
+        for i in (0..self.limbs.len()).rev() {
+            let cur = (rem << 32) | self.limbs[i] as u128;
+            quotient[i] = (cur / divisor as u128) as u32;
+            rem = cur % divisor as u128;
+        }
+        let mut big = Self { limbs: quotient };
+        big.trim();
+        return (big, rem as u64);
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()));
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 1_000_000_000_000_000_000;
+        let mut chunks: Vec<u64> = Vec::new();
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, rem) = rest.div_rem_u64(CHUNK);
+            chunks.push(rem);
+            rest = quotient;
+        }
+        match chunks.pop() {
+            None => write!(f, "0"),
+            Some(top) => {
+                write!(f, "{}", top)?;
+                for c in chunks.iter().rev() {
+                    write!(f, "{:018}", c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(7)]
+    #[test_case(u32::MAX as u64 + 1)]
+    #[test_case(u64::MAX)]
+    fn test_u64_round_trip(value: u64) {
+        let big = BigUint::from_u64(value);
+        assert_eq!(big.to_u64(), Some(value));
+        assert_eq!(big.to_string(), value.to_string());
+        assert_eq!(big.bits(), 64 - value.leading_zeros() as u64);
+    }
+
+    #[test]
+    fn test_arithmetic_past_u64() {
+        let max = BigUint::from_u64(u64::MAX);
+        let squared = max.mul(&max);
+        assert_eq!(squared.to_u64(), None);
+        assert_eq!(squared.bits(), 128);
+        assert_eq!(
+            squared.to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        let plus_one = max.add(&BigUint::from_u64(1));
+        assert_eq!(plus_one.to_string(), "18446744073709551616");
+        assert_eq!(
+            plus_one.div_rem_u64(3),
+            (BigUint::from_u64(6148914691236517205), 1)
+        );
+        assert!(plus_one > max);
+    }
+}
@@ -1,118 +1,684 @@
-use std::{collections::VecDeque, fmt::Debug};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+};
 
-pub fn compute_input_inspection_product(rounds: u32, stress_reduction_factor: u32) -> u64 {
-    let mut monkeys = vec![
+use self::big_uint::BigUint;
+
+mod big_uint;
+
+pub fn compute_input_inspection_product(
+    rounds: u32,
+    stress_reduction_factor: u32,
+) -> Result<u64, String> {
+    let mut monkeys = build_input_monkeys();
+    let relief = DivideRelief(stress_reduction_factor as u64);
+    return compute_inspection_product(&mut monkeys, rounds, &relief, 2);
+}
+
+pub fn report_input_monkeys(
+    rounds: u32,
+    relief: &dyn WorryRelief,
+    snapshot_every: u32,
+) -> MonkeyReport {
+    let mut monkeys = build_input_monkeys();
+    return simulate(&mut monkeys, rounds, relief, snapshot_every);
+}
+
+fn build_input_monkeys() -> Vec<Monkey> {
+    return vec![
         Monkey {
             items: VecDeque::from([71, 86]),
             inspection_count: 0,
-            calc_new: |old| old * 13,
-            get_next_monkey: |val| if val % 19 == 0 { 6 } else { 7 },
+            operation: Operation::Multiply(13),
+            test_divisor: 19,
+            if_true: 6,
+            if_false: 7,
         },
         Monkey {
             items: VecDeque::from([66, 50, 90, 53, 88, 85]),
             inspection_count: 0,
-            calc_new: |old| old + 3,
-            get_next_monkey: |val| if val % 2 == 0 { 5 } else { 4 },
+            operation: Operation::Add(3),
+            test_divisor: 2,
+            if_true: 5,
+            if_false: 4,
         },
         Monkey {
             items: VecDeque::from([97, 54, 89, 62, 84, 80, 63]),
             inspection_count: 0,
-            calc_new: |old| old + 6,
-            get_next_monkey: |val| if val % 13 == 0 { 4 } else { 1 },
+            operation: Operation::Add(6),
+            test_divisor: 13,
+            if_true: 4,
+            if_false: 1,
         },
         Monkey {
             items: VecDeque::from([82, 97, 56, 92]),
             inspection_count: 0,
-            calc_new: |old| old + 2,
-            get_next_monkey: |val| if val % 5 == 0 { 6 } else { 0 },
+            operation: Operation::Add(2),
+            test_divisor: 5,
+            if_true: 6,
+            if_false: 0,
         },
         Monkey {
             items: VecDeque::from([50, 99, 67, 61, 86]),
             inspection_count: 0,
-            calc_new: |old| old * old,
-            get_next_monkey: |val| if val % 7 == 0 { 5 } else { 3 },
+            operation: Operation::Square,
+            test_divisor: 7,
+            if_true: 5,
+            if_false: 3,
         },
         Monkey {
             items: VecDeque::from([61, 66, 72, 55, 64, 53, 72, 63]),
             inspection_count: 0,
-            calc_new: |old| old + 4,
-            get_next_monkey: |val| if val % 11 == 0 { 3 } else { 0 },
+            operation: Operation::Add(4),
+            test_divisor: 11,
+            if_true: 3,
+            if_false: 0,
         },
         Monkey {
             items: VecDeque::from([59, 79, 63]),
             inspection_count: 0,
-            calc_new: |old| old * 7,
-            get_next_monkey: |val| if val % 17 == 0 { 2 } else { 7 },
+            operation: Operation::Multiply(7),
+            test_divisor: 17,
+            if_true: 2,
+            if_false: 7,
         },
         Monkey {
             items: VecDeque::from([55]),
             inspection_count: 0,
-            calc_new: |old| old + 7,
-            get_next_monkey: |val| if val % 3 == 0 { 2 } else { 1 },
+            operation: Operation::Add(7),
+            test_divisor: 3,
+            if_true: 2,
+            if_false: 1,
         },
     ];
-
-    let mod_product = vec![19, 2, 13, 5, 7, 11, 17, 3].iter().product();
-    return compute_inspection_product(&mut monkeys, rounds, stress_reduction_factor as u64, mod_product);
 }
 
 fn compute_inspection_product(
     monkeys: &mut Vec<Monkey>,
     rounds: u32,
-    stress_reduction_factor: u64,
-    mod_product: u64,
-) -> u64 {
-    println!("Mod Product: {:?} stress_reduction_factor: {:?}", mod_product, stress_reduction_factor);
+    relief: &dyn WorryRelief,
+    top_k: usize,
+) -> Result<u64, String> {
+    let report = simulate(monkeys, rounds, relief, 0);
+    println!("Inspection counts: {:?}", report.inspection_counts);
+    if let Some(round) = report.overflowed_at {
+        return Err(format!("Worry overflowed u64 in round {}", round));
+    }
+    return Ok(report.top_k_product(top_k));
+}
+
+// Runs every round, recording held items and counts every `snapshot_every`
+// rounds (0 keeps only the final state). Stops partway through the round
+// in which a worry first overflows u64.
+fn simulate(
+    monkeys: &mut Vec<Monkey>,
+    rounds: u32,
+    relief: &dyn WorryRelief,
+    snapshot_every: u32,
+) -> MonkeyReport {
+    let modulus = if relief.allows_modular_reduction() {
+        Some(worry_modulus(monkeys))
+    } else {
+        None
+    };
+    println!("Modulus: {:?} relief: {}", modulus, relief.name());
 
-    for i in 0..rounds {
-        println!("Round {}", i);
-        run_round_for_monkeys(monkeys, stress_reduction_factor, mod_product);
+    let mut snapshots: Vec<RoundSnapshot> = Vec::new();
+    let mut overflowed_at = None;
+    for round in 1..=rounds {
+        if run_round_for_monkeys(monkeys, relief, modulus).is_err() {
+            overflowed_at = Some(round);
+            break;
+        }
+        if snapshot_every > 0 && (round % snapshot_every == 0 || round == rounds) {
+            snapshots.push(RoundSnapshot::capture(round, monkeys));
+        }
     }
 
-    let mut inspection_counts: Vec<u64> = monkeys.into_iter().map(|m| m.inspection_count).collect();
-    inspection_counts.sort();
-    inspection_counts.reverse();
-    println!("Inspection counts: {:?}", inspection_counts);
-    let product = inspection_counts[..2].iter().product();
-    return product;
+    return MonkeyReport {
+        snapshots,
+        inspection_counts: monkeys.iter().map(|m| m.inspection_count).collect(),
+        overflowed_at,
+    };
+}
+
+// Every throw only depends on worry modulo each monkey's divisor, so worry
+// can be kept modulo their lcm as long as relief doesn't divide.
+fn worry_modulus(monkeys: &[Monkey]) -> u64 {
+    let gcd = |mut a: u64, mut b: u64| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        return a;
+    };
+    return monkeys
+        .iter()
+        .map(|m| m.test_divisor)
+        .fold(1, |acc, d| acc / gcd(acc, d) * d);
 }
 
 fn run_round_for_monkeys(
-    monkeys: &mut Vec<Monkey>,
-    stress_reduction_factor: u64,
-    mod_product: u64,
-) {
+    monkeys: &mut [Monkey],
+    relief: &dyn WorryRelief,
+    modulus: Option<u64>,
+) -> Result<(), String> {
     for i in 0..monkeys.len() {
-        println!("\t {:?}", monkeys[i]);
-        while let Some((next_monkey, value)) =
-            monkeys[i].process_next_item(stress_reduction_factor, mod_product)
-        {
+        while let Some((next_monkey, value)) = monkeys[i].process_next_item(relief, modulus)? {
             monkeys[next_monkey].items.push_back(value);
         }
     }
+    return Ok(());
+}
+
+// "3" or "div:3" divides by 3, "cap:1000" caps worry at 1000.
+pub fn parse_relief(spec: &str) -> Result<Box<dyn WorryRelief>, String> {
+    let (kind, value) = spec.split_once(':').unwrap_or(("div", spec));
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Relief value not parsable: {:?}", spec))?;
+    return match kind {
+        "div" if value > 0 => Ok(Box::new(DivideRelief(value))),
+        "cap" => Ok(Box::new(CapRelief(value))),
+        _ => Err(format!("Unknown relief policy: {:?}", spec)),
+    };
+}
+
+pub trait WorryRelief {
+    fn name(&self) -> String;
+    fn relieve(&self, worry: u64) -> u64;
+    fn relieve_exact(&self, worry: Worry) -> Worry;
+    // Only safe when relief commutes with taking the worry modulo a number.
+    fn allows_modular_reduction(&self) -> bool;
+}
+
+// Part 1 divides by 3 after each inspection; part 2 "divides" by 1.
+pub struct DivideRelief(pub u64);
+
+impl WorryRelief for DivideRelief {
+    fn name(&self) -> String {
+        return format!("divide by {}", self.0);
+    }
+
+    fn relieve(&self, worry: u64) -> u64 {
+        return worry / self.0;
+    }
+
+    fn relieve_exact(&self, worry: Worry) -> Worry {
+        return worry.div_u64(self.0);
+    }
+
+    fn allows_modular_reduction(&self) -> bool {
+        return self.0 == 1;
+    }
+}
+
+// Caps worry at a ceiling, for trying out house rules.
+pub struct CapRelief(pub u64);
+
+impl WorryRelief for CapRelief {
+    fn name(&self) -> String {
+        return format!("cap at {}", self.0);
+    }
+
+    fn relieve(&self, worry: u64) -> u64 {
+        return worry.min(self.0);
+    }
+
+    fn relieve_exact(&self, worry: Worry) -> Worry {
+        return match worry {
+            Worry::Small(val) => Worry::Small(val.min(self.0)),
+            Worry::Big(_) => Worry::Small(self.0),
+        };
+    }
+
+    fn allows_modular_reduction(&self) -> bool {
+        return false;
+    }
+}
+
+// Past this a single squaring takes longer than is reasonable to wait for.
+const MAX_EXACT_BITS: u64 = 1 << 16;
+
+// Like `simulate` but never takes a modulus: worry stays a u64 while checked
+// arithmetic allows it and becomes a `BigUint` once it would overflow. Big
+// worries double in length with every squaring, so the run gives up once
+// one passes MAX_EXACT_BITS.
+fn simulate_exact(
+    monkeys: &[Monkey],
+    rounds: u32,
+    relief: &dyn WorryRelief,
+) -> Result<ExactReport, String> {
+    let mut items: Vec<VecDeque<Worry>> = monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|val| Worry::Small(*val)).collect())
+        .collect();
+    let mut inspection_counts: Vec<u64> = monkeys.iter().map(|m| m.inspection_count).collect();
+    let mut overflowed = false;
+
+    for round in 1..=rounds {
+        for i in 0..monkeys.len() {
+            while let Some(old) = items[i].pop_front() {
+                let inspected = monkeys[i].operation.apply_exact(&old);
+                if let Worry::Big(big) = &inspected {
+                    if big.bits() > MAX_EXACT_BITS {
+                        return Err(format!(
+                            "Worry grew past {} bits in round {}, too big to follow exactly",
+                            MAX_EXACT_BITS, round
+                        ));
+                    }
+                    overflowed = true;
+                }
+                let new = relief.relieve_exact(inspected);
+                let next_monkey = monkeys[i].get_next_monkey(new.rem_u64(monkeys[i].test_divisor));
+                inspection_counts[i] += 1;
+                items[next_monkey].push_back(new);
+            }
+        }
+    }
+
+    return Ok(ExactReport {
+        inspection_counts,
+        held_items: items.into_iter().map(Vec::from).collect(),
+        overflowed,
+        u64_overflow_round: None,
+    });
+}
+
+// Runs the usual simulation and the exact one side by side. Counts must
+// match, and when a modulus was taken each exact worry must reduce to the
+// modular one. If the u64 run overflows, only the rounds before it are
+// compared and the exact run carries on alone.
+fn cross_check(
+    monkeys: &[Monkey],
+    rounds: u32,
+    relief: &dyn WorryRelief,
+) -> Result<ExactReport, String> {
+    let modular = simulate(&mut monkeys.to_vec(), rounds, relief, rounds.max(1));
+    if let Some(round) = modular.overflowed_at {
+        cross_check(monkeys, round - 1, relief)?;
+        let mut exact = simulate_exact(monkeys, rounds, relief)?;
+        exact.u64_overflow_round = Some(round);
+        return Ok(exact);
+    }
+
+    let exact = simulate_exact(monkeys, rounds, relief)?;
+    if exact.inspection_counts != modular.inspection_counts {
+        return Err(format!(
+            "Inspection counts differ after {} rounds: exact {:?} modular {:?}",
+            rounds, exact.inspection_counts, modular.inspection_counts
+        ));
+    }
+
+    let modulus = worry_modulus(monkeys);
+    let held = modular.snapshots.last().map(|s| s.held_items.clone());
+    let held = held.unwrap_or_else(|| {
+        monkeys
+            .iter()
+            .map(|m| m.items.iter().cloned().collect())
+            .collect()
+    });
+    for (i, (exact_items, modular_items)) in exact.held_items.iter().zip(held.iter()).enumerate() {
+        let agrees = exact_items.len() == modular_items.len()
+            && exact_items.iter().zip(modular_items).all(|(e, m)| {
+                if relief.allows_modular_reduction() {
+                    e.rem_u64(modulus) == *m
+                } else {
+                    *e == Worry::Small(*m)
+                }
+            });
+        if !agrees {
+            return Err(format!(
+                "Monkey {} holds different items after {} rounds: exact {:?} modular {:?}",
+                i, rounds, exact_items, modular_items
+            ));
+        }
+    }
+    return Ok(exact);
+}
+
+pub fn cross_check_input_monkeys(
+    rounds: u32,
+    relief: &dyn WorryRelief,
+) -> Result<ExactReport, String> {
+    return cross_check(&build_input_monkeys(), rounds, relief);
+}
+
+// Part 2 monkey business after any number of rounds, without running them.
+pub fn fast_forward_input_inspection_product(rounds: u64) -> Result<u128, String> {
+    let mut counts = fast_forward_inspection_counts(&build_input_monkeys(), rounds)?;
+    println!("Inspection counts: {:?}", counts);
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    return Ok(counts.iter().take(2).map(|c| *c as u128).product());
+}
+
+// Inspection counts after `rounds` rounds with no relief (part 2 rules).
+// Items never affect each other, and an item's (worry mod the modulus,
+// monkey) state at the start of a round decides every later round, so each
+// item's rounds eventually cycle. Brent's algorithm finds the cycle in
+// constant memory and the counts for whole cycles are multiplied out.
+fn fast_forward_inspection_counts(monkeys: &[Monkey], rounds: u64) -> Result<Vec<u64>, String> {
+    let modulus = worry_modulus(monkeys);
+    let mut counts = vec![0; monkeys.len()];
+    for (monkey, item) in monkeys
+        .iter()
+        .enumerate()
+        .flat_map(|(i, m)| m.items.iter().map(move |item| (i, *item)))
+    {
+        let path = ItemPath { monkeys, modulus };
+        let start = (item % modulus, monkey);
+        let (lead_in, cycle_len) = path.find_cycle(start)?;
+
+        let mut state = start;
+        if rounds <= lead_in + cycle_len {
+            path.run(&mut state, rounds, &mut counts)?;
+            continue;
+        }
+        path.run(&mut state, lead_in, &mut counts)?;
+        let mut cycle_counts = vec![0; monkeys.len()];
+        path.run(&mut state, cycle_len, &mut cycle_counts)?;
+        let (cycles, rest) = (
+            (rounds - lead_in) / cycle_len,
+            (rounds - lead_in) % cycle_len,
+        );
+        for (i, (count, per_cycle)) in counts.iter_mut().zip(cycle_counts).enumerate() {
+            *count = per_cycle
+                .checked_mul(cycles)
+                .and_then(|c| c.checked_add(*count))
+                .ok_or(format!(
+                    "Monkey {} inspects more than {} items in {} rounds",
+                    i,
+                    u64::MAX,
+                    rounds
+                ))?;
+        }
+        path.run(&mut state, rest, &mut counts)?;
+    }
+    return Ok(counts);
+}
+
+// Follows a single item round by round, worry kept modulo `modulus`.
+struct ItemPath<'a> {
+    monkeys: &'a [Monkey],
+    modulus: u64,
+}
+
+impl ItemPath<'_> {
+    // Monkeys take turns in order, so an item thrown to a later monkey is
+    // inspected again in the same round.
+    fn next_round(
+        &self,
+        (mut worry, mut monkey): (u64, usize),
+        counts: &mut [u64],
+    ) -> Result<(u64, usize), String> {
+        loop {
+            let m = &self.monkeys[monkey];
+            counts[monkey] = counts[monkey].checked_add(1).ok_or(format!(
+                "Monkey {} inspects more than {} items",
+                monkey,
+                u64::MAX
+            ))?;
+            worry = m.operation.checked_apply(worry).ok_or(format!(
+                "Worry modulus {} is too large to follow in a u64",
+                self.modulus
+            ))? % self.modulus;
+            let next_monkey = m.get_next_monkey(worry);
+            if next_monkey < monkey {
+                return Ok((worry, next_monkey));
+            }
+            monkey = next_monkey;
+        }
+    }
+
+    fn run(&self, state: &mut (u64, usize), rounds: u64, counts: &mut [u64]) -> Result<(), String> {
+        for _ in 0..rounds {
+            *state = self.next_round(*state, counts)?;
+        }
+        return Ok(());
+    }
+
+    // Brent's cycle detection: (rounds before the cycle, cycle length).
+    fn find_cycle(&self, start: (u64, usize)) -> Result<(u64, u64), String> {
+        let mut scratch = vec![0; self.monkeys.len()];
+        let mut step = |state| self.next_round(state, &mut scratch);
+
+        let (mut power, mut cycle_len) = (1, 1);
+        let mut tortoise = start;
+        let mut hare = step(start)?;
+        while tortoise != hare {
+            if power == cycle_len {
+                tortoise = hare;
+                power *= 2;
+                cycle_len = 0;
+            }
+            hare = step(hare)?;
+            cycle_len += 1;
+        }
+
+        let (mut tortoise, mut hare) = (start, start);
+        for _ in 0..cycle_len {
+            hare = step(hare)?;
+        }
+        let mut lead_in = 0;
+        while tortoise != hare {
+            tortoise = step(tortoise)?;
+            hare = step(hare)?;
+            lead_in += 1;
+        }
+        return Ok((lead_in, cycle_len));
+    }
+}
+
+// A worry level that stays a u64 until an operation would overflow it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Worry {
+    Small(u64),
+    Big(BigUint),
+}
+
+impl Worry {
+    fn from_big(big: BigUint) -> Self {
+        return match big.to_u64() {
+            Some(val) => Worry::Small(val),
+            None => Worry::Big(big),
+        };
+    }
+
+    fn to_big(&self) -> BigUint {
+        return match self {
+            Worry::Small(val) => BigUint::from_u64(*val),
+            Worry::Big(big) => big.clone(),
+        };
+    }
+
+    pub fn rem_u64(&self, divisor: u64) -> u64 {
+        return match self {
+            Worry::Small(val) => val % divisor,
+            Worry::Big(big) => big.div_rem_u64(divisor).1,
+        };
+    }
+
+    pub fn div_u64(&self, divisor: u64) -> Worry {
+        return match self {
+            Worry::Small(val) => Worry::Small(val / divisor),
+            Worry::Big(big) => Worry::from_big(big.div_rem_u64(divisor).0),
+        };
+    }
+}
+
+impl fmt::Display for Worry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Worry::Small(val) => write!(f, "{}", val),
+            Worry::Big(big) => write!(f, "{}", big),
+        };
+    }
+}
+
+pub struct ExactReport {
+    pub inspection_counts: Vec<u64>,
+    pub held_items: Vec<Vec<Worry>>,
+    // Whether any worry ever needed more than a u64.
+    pub overflowed: bool,
+    // The round the plain u64 run overflowed in, if it did.
+    pub u64_overflow_round: Option<u32>,
+}
+
+impl fmt::Display for ExactReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, items) in self.held_items.iter().enumerate() {
+            let digits: Vec<usize> = items.iter().map(|w| w.to_string().len()).collect();
+            writeln!(
+                f,
+                "Monkey {} holds worries with digit counts {:?}",
+                i, digits
+            )?;
+        }
+        writeln!(f, "Inspection counts: {:?}", self.inspection_counts)?;
+        if let Some(round) = self.u64_overflow_round {
+            writeln!(
+                f,
+                "u64 overflowed at round {}, checked through round {}",
+                round,
+                round - 1
+            )?;
+        }
+        write!(f, "Overflowed u64: {}", self.overflowed)
+    }
+}
+
+pub struct RoundSnapshot {
+    pub round: u32,
+    pub inspection_counts: Vec<u64>,
+    pub held_items: Vec<Vec<u64>>,
+}
+
+impl RoundSnapshot {
+    fn capture(round: u32, monkeys: &[Monkey]) -> Self {
+        return Self {
+            round,
+            inspection_counts: monkeys.iter().map(|m| m.inspection_count).collect(),
+            held_items: monkeys
+                .iter()
+                .map(|m| m.items.iter().cloned().collect())
+                .collect(),
+        };
+    }
+}
+
+pub struct MonkeyReport {
+    pub snapshots: Vec<RoundSnapshot>,
+    pub inspection_counts: Vec<u64>,
+    // The round a worry overflowed u64 in, which the run stopped during.
+    pub overflowed_at: Option<u32>,
+}
+
+impl MonkeyReport {
+    // Product of the `k` highest inspection counts ("monkey business" is k = 2).
+    pub fn top_k_product(&self, k: usize) -> u64 {
+        let mut counts = self.inspection_counts.clone();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        return counts.iter().take(k).product();
+    }
+}
+
+impl fmt::Display for MonkeyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for snapshot in &self.snapshots {
+            writeln!(f, "== After round {} ==", snapshot.round)?;
+            for (i, items) in snapshot.held_items.iter().enumerate() {
+                writeln!(
+                    f,
+                    "Monkey {} (inspected {}): {:?}",
+                    i, snapshot.inspection_counts[i], items
+                )?;
+            }
+        }
+        if let Some(round) = self.overflowed_at {
+            writeln!(f, "Worry overflowed u64 in round {}, stopped there", round)?;
+        }
+        writeln!(f, "Inspection counts: {:?}", self.inspection_counts)?;
+        write!(
+            f,
+            "Top 2 product: {} Top 3 product: {}",
+            self.top_k_product(2),
+            self.top_k_product(3)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operation {
+    Add(u64),
+    Multiply(u64),
+    Square,
+}
+
+impl Operation {
+    // None when the new worry doesn't fit in a u64.
+    fn checked_apply(&self, old: u64) -> Option<u64> {
+        return match self {
+            Operation::Add(n) => old.checked_add(*n),
+            Operation::Multiply(n) => old.checked_mul(*n),
+            Operation::Square => old.checked_mul(old),
+        };
+    }
+
+    fn apply_exact(&self, old: &Worry) -> Worry {
+        if let Worry::Small(val) = old {
+            if let Some(new) = self.checked_apply(*val) {
+                return Worry::Small(new);
+            }
+        }
+        let old = old.to_big();
+        return Worry::from_big(match self {
+            Operation::Add(n) => old.add(&BigUint::from_u64(*n)),
+            Operation::Multiply(n) => old.mul(&BigUint::from_u64(*n)),
+            Operation::Square => old.mul(&old),
+        });
+    }
 }
 
 #[derive(Clone)]
 struct Monkey {
     items: VecDeque<u64>,
     inspection_count: u64,
-    calc_new: fn(u64) -> u64,
-    get_next_monkey: fn(u64) -> usize,
+    operation: Operation,
+    test_divisor: u64,
+    if_true: usize,
+    if_false: usize,
 }
 
 impl Monkey {
     fn process_next_item(
         &mut self,
-        stress_reduction_factor: u64,
-        mod_product: u64,
-    ) -> Option<(usize, u64)> {
-        let old = self.items.pop_front()?;
-        let new = (self.calc_new)(old);
-        let bored_val = (new / stress_reduction_factor) % mod_product; // stress_reduction_factor as u32;
-        let next_monkey = (self.get_next_monkey)(bored_val);
+        relief: &dyn WorryRelief,
+        modulus: Option<u64>,
+    ) -> Result<Option<(usize, u64)>, String> {
+        let Some(old) = self.items.pop_front() else {
+            return Ok(None);
+        };
+        let new = self.operation.checked_apply(old).ok_or(format!(
+            "Worry {} overflowed u64 under {:?}, use exact mode",
+            old, self.operation
+        ))?;
+        let relieved = relief.relieve(new);
+        let bored_val = match modulus {
+            Some(m) => relieved % m,
+            None => relieved,
+        };
+        let next_monkey = self.get_next_monkey(bored_val);
         // println!("{:?} {:?} {:?} {:?}", old, new, bored_val, next_monkey);
         self.inspection_count += 1;
-        return Some((next_monkey, bored_val));
+        return Ok(Some((next_monkey, bored_val)));
+    }
+
+    fn get_next_monkey(&self, val: u64) -> usize {
+        return if val % self.test_divisor == 0 {
+            self.if_true
+        } else {
+            self.if_false
+        };
     }
 }
 
@@ -124,35 +690,44 @@ impl Debug for Monkey {
 
 #[cfg(test)]
 mod tests {
-    use std::{vec, u64::MAX};
+    use std::vec;
 
     use super::*;
+    use test_case::test_case;
 
     fn build_sample_monkeys() -> Vec<Monkey> {
         return vec![
             Monkey {
                 items: VecDeque::from([79, 98]),
                 inspection_count: 0,
-                calc_new: |old| old * 19,
-                get_next_monkey: |val| if val % 23 == 0 { 2 } else { 3 },
+                operation: Operation::Multiply(19),
+                test_divisor: 23,
+                if_true: 2,
+                if_false: 3,
             },
             Monkey {
                 items: VecDeque::from([54, 65, 75, 74]),
                 inspection_count: 0,
-                calc_new: |old| old + 6,
-                get_next_monkey: |val| if val % 19 == 0 { 2 } else { 0 },
+                operation: Operation::Add(6),
+                test_divisor: 19,
+                if_true: 2,
+                if_false: 0,
             },
             Monkey {
                 items: VecDeque::from([79, 60, 97]),
                 inspection_count: 0,
-                calc_new: |old| old * old,
-                get_next_monkey: |val| if val % 13 == 0 { 1 } else { 3 },
+                operation: Operation::Square,
+                test_divisor: 13,
+                if_true: 1,
+                if_false: 3,
             },
             Monkey {
                 items: VecDeque::from([74]),
                 inspection_count: 0,
-                calc_new: |old| old + 3,
-                get_next_monkey: |val| if val % 17 == 0 { 0 } else { 1 },
+                operation: Operation::Add(3),
+                test_divisor: 17,
+                if_true: 0,
+                if_false: 1,
             },
         ];
     }
@@ -161,16 +736,25 @@ mod tests {
     fn test_process_item() {
         let mut monkeys = build_sample_monkeys();
 
-        assert_eq!(monkeys[0].process_next_item(3, MAX), Some((3, 500)));
-        assert_eq!(monkeys[0].process_next_item(3, MAX), Some((3, 620)));
-        assert_eq!(monkeys[0].process_next_item(3, MAX), None);
+        assert_eq!(
+            monkeys[0].process_next_item(&DivideRelief(3), None),
+            Ok(Some((3, 500)))
+        );
+        assert_eq!(
+            monkeys[0].process_next_item(&DivideRelief(3), None),
+            Ok(Some((3, 620)))
+        );
+        assert_eq!(
+            monkeys[0].process_next_item(&DivideRelief(3), None),
+            Ok(None)
+        );
         assert_eq!(monkeys[0].inspection_count, 2);
     }
 
     #[test]
     fn test_monkey_round() {
         let mut monkeys = build_sample_monkeys();
-        run_round_for_monkeys(&mut monkeys, 3, MAX);
+        run_round_for_monkeys(&mut monkeys, &DivideRelief(3), None).unwrap();
         assert_eq!(monkeys[0].items, VecDeque::from([20, 23, 27, 26]));
         assert_eq!(
             monkeys[1].items,
@@ -179,7 +763,7 @@ mod tests {
         assert_eq!(monkeys[2].items, VecDeque::from([]));
         assert_eq!(monkeys[3].items, VecDeque::from([]));
 
-        run_round_for_monkeys(&mut monkeys, 3, MAX);
+        run_round_for_monkeys(&mut monkeys, &DivideRelief(3), None).unwrap();
         assert_eq!(monkeys[0].items, VecDeque::from([695, 10, 71, 135, 350]));
         assert_eq!(monkeys[1].items, VecDeque::from([43, 49, 58, 55, 362]));
         assert_eq!(monkeys[2].items, VecDeque::from([]));
@@ -189,15 +773,259 @@ mod tests {
     #[test]
     fn test_sample_inspection_product() {
         let mut monkeys = build_sample_monkeys();
-        let result = compute_inspection_product(&mut monkeys, 20, 3, MAX);
-        assert_eq!(result, 10605);
+        let result = compute_inspection_product(&mut monkeys, 20, &DivideRelief(3), 2);
+        assert_eq!(result, Ok(10605));
     }
 
     #[test]
     fn test_pt2_product() {
         let mut monkeys = build_sample_monkeys();
-        let mod_product = 23 * 19 * 13 * 17;
-        let result = compute_inspection_product(&mut monkeys, 10000, 1, mod_product);
-        assert_eq!(result, 2713310158);
+        let result = compute_inspection_product(&mut monkeys, 10000, &DivideRelief(1), 2);
+        assert_eq!(result, Ok(2713310158));
+    }
+
+    #[test]
+    fn test_worry_modulus() {
+        let monkeys = build_sample_monkeys();
+        assert_eq!(worry_modulus(&monkeys), 23 * 19 * 13 * 17);
+        assert!(DivideRelief(1).allows_modular_reduction());
+        assert!(!DivideRelief(3).allows_modular_reduction());
+    }
+
+    #[test]
+    fn test_round_report() {
+        let mut monkeys = build_sample_monkeys();
+        let report = simulate(&mut monkeys, 20, &DivideRelief(3), 10);
+        assert_eq!(
+            report
+                .snapshots
+                .iter()
+                .map(|s| s.round)
+                .collect::<Vec<u32>>(),
+            vec![10, 20]
+        );
+        assert_eq!(
+            report.snapshots[1].held_items,
+            vec![
+                vec![10, 12, 14, 26, 34],
+                vec![245, 93, 53, 199, 115],
+                vec![],
+                vec![]
+            ]
+        );
+        assert_eq!(report.inspection_counts, vec![101, 95, 7, 105]);
+        assert_eq!(report.top_k_product(2), 10605);
+        assert_eq!(report.top_k_product(3), 105 * 101 * 95);
+        assert_eq!(report.top_k_product(10), 105 * 101 * 95 * 7);
+    }
+
+    #[test]
+    fn test_pt2_sample_counts_after_1000_rounds() {
+        let mut monkeys = build_sample_monkeys();
+        let report = simulate(&mut monkeys, 1000, &DivideRelief(1), 0);
+        assert_eq!(report.inspection_counts, vec![5204, 4792, 199, 5192]);
+    }
+
+    #[test_case("3", "divide by 3", false)]
+    #[test_case("div:1", "divide by 1", true)]
+    #[test_case("cap:1000", "cap at 1000", false)]
+    fn test_parse_relief(spec: &str, expected_name: &str, expected_modular: bool) {
+        let relief = parse_relief(spec).unwrap();
+        assert_eq!(relief.name(), expected_name);
+        assert_eq!(relief.allows_modular_reduction(), expected_modular);
+    }
+
+    #[test_case("div:0")]
+    #[test_case("half")]
+    #[test_case("sqrt:2")]
+    fn test_parse_bad_relief(spec: &str) {
+        assert!(parse_relief(spec).is_err());
+    }
+
+    #[test]
+    fn test_exact_mode_matches_modular_results() {
+        let monkeys = build_sample_monkeys();
+        for rounds in [0, 1, 12, 13, 16] {
+            let exact = cross_check(&monkeys, rounds, &DivideRelief(1)).unwrap();
+            assert_eq!(exact.overflowed, rounds >= 13);
+        }
+        let exact = cross_check(&monkeys, 20, &DivideRelief(3)).unwrap();
+        assert_eq!(exact.inspection_counts, vec![101, 95, 7, 105]);
+        assert!(!exact.overflowed);
+    }
+
+    // Without a modulus the u64 run overflows, which exact mode has to
+    // report rather than crash on.
+    #[test]
+    fn test_exact_mode_past_u64_overflow() {
+        let monkeys = build_sample_monkeys();
+        let plain = simulate(&mut monkeys.clone(), 100, &DivideRelief(3), 0);
+        let round = plain.overflowed_at.unwrap();
+        assert_eq!(round, 92);
+
+        let exact = cross_check(&monkeys, 100, &DivideRelief(3)).unwrap();
+        assert_eq!(exact.u64_overflow_round, Some(round));
+        assert!(exact.overflowed);
+        assert!(exact
+            .to_string()
+            .contains(&format!("u64 overflowed at round {}", round)));
+        let before = cross_check(&monkeys, round - 1, &DivideRelief(3)).unwrap();
+        assert!(before
+            .inspection_counts
+            .iter()
+            .zip(&exact.inspection_counts)
+            .all(|(b, e)| b < e));
+        assert_eq!(
+            compute_inspection_product(&mut monkeys.clone(), 100, &DivideRelief(3), 2),
+            Err(format!("Worry overflowed u64 in round {}", round))
+        );
+    }
+
+    #[test]
+    fn test_exact_mode_gives_up_on_huge_worries() {
+        let result = cross_check(&build_sample_monkeys(), 1000, &DivideRelief(3));
+        assert!(result.is_err_and(|e| e.starts_with("Worry grew past")));
+    }
+
+    #[test]
+    fn test_exact_operation_past_u64() {
+        let old = Worry::Small(u64::MAX);
+        let squared = Operation::Square.apply_exact(&old);
+        assert_eq!(
+            squared.to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(squared.rem_u64(10), 5);
+        assert_eq!(
+            Operation::Add(1).apply_exact(&Worry::Small(1)),
+            Worry::Small(2)
+        );
+        assert_eq!(CapRelief(7).relieve_exact(squared), Worry::Small(7));
+        assert_eq!(Operation::Square.checked_apply(1 << 32), None);
+    }
+
+    #[test]
+    fn test_fast_forward_matches_simulation() {
+        let monkeys = build_sample_monkeys();
+        for rounds in [0, 1, 7, 20, 1000, 10000] {
+            let report = simulate(&mut monkeys.clone(), rounds, &DivideRelief(1), 0);
+            let counts = fast_forward_inspection_counts(&monkeys, rounds as u64);
+            assert_eq!(counts, Ok(report.inspection_counts), "{} rounds", rounds);
+        }
+    }
+
+    // The longest lead-in before any item starts cycling, the longest
+    // cycle and the period after which every item is back where it was.
+    fn item_cycles(monkeys: &[Monkey]) -> (u64, u64, u64) {
+        let modulus = worry_modulus(monkeys);
+        let path = ItemPath { monkeys, modulus };
+        let gcd = |mut a: u64, mut b: u64| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            return a;
+        };
+        let (mut lead_in, mut longest, mut period) = (0, 1, 1);
+        for (i, m) in monkeys.iter().enumerate() {
+            for item in &m.items {
+                let (item_lead_in, cycle_len) = path.find_cycle((item % modulus, i)).unwrap();
+                lead_in = lead_in.max(item_lead_in);
+                longest = longest.max(cycle_len);
+                period = period / gcd(period, cycle_len) * cycle_len;
+            }
+        }
+        return (lead_in, longest, period);
+    }
+
+    #[test]
+    fn test_fast_forward_over_a_few_cycles() {
+        let monkeys = build_sample_monkeys();
+        let (lead_in, longest, _) = item_cycles(&monkeys);
+        let rounds = lead_in + 3 * longest + 5;
+        let report = simulate(&mut monkeys.clone(), rounds as u32, &DivideRelief(1), 0);
+        assert_eq!(
+            fast_forward_inspection_counts(&monkeys, rounds),
+            Ok(report.inspection_counts)
+        );
+    }
+
+    // Once every item is cycling, each monkey's count grows by the same
+    // amount every period, so the counts after a huge number of rounds
+    // follow from two short direct runs.
+    #[test]
+    fn test_fast_forward_huge_round_count() {
+        let monkeys = build_sample_monkeys();
+        let (lead_in, _, period) = item_cycles(&monkeys);
+        let rounds: u64 = 1_000_000_000_000;
+        let start = lead_in + (rounds - lead_in) % period;
+        let mut direct = monkeys.clone();
+        let before = simulate(&mut direct, start as u32, &DivideRelief(1), 0).inspection_counts;
+        let after = simulate(&mut direct, period as u32, &DivideRelief(1), 0).inspection_counts;
+        let cycles = (rounds - start) / period;
+        let expected: Vec<u64> = before
+            .iter()
+            .zip(&after)
+            .map(|(b, a)| b + (a - b) * cycles)
+            .collect();
+        assert_eq!(
+            fast_forward_inspection_counts(&monkeys, rounds),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_count_overflow() {
+        let counts = fast_forward_inspection_counts(&build_sample_monkeys(), u64::MAX);
+        assert!(counts.is_err_and(|e| e.contains("more than")));
+
+        let monkeys = build_sample_monkeys();
+        let path = ItemPath {
+            monkeys: &monkeys,
+            modulus: worry_modulus(&monkeys),
+        };
+        let mut counts = vec![u64::MAX; monkeys.len()];
+        assert_eq!(
+            path.run(&mut (79, 0), 1, &mut counts),
+            Err(format!("Monkey 0 inspects more than {} items", u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_modulus_too_large() {
+        // Squaring anything below a 2^40 modulus can overflow a u64.
+        let monkeys = vec![
+            Monkey {
+                items: VecDeque::from([1 << 33]),
+                inspection_count: 0,
+                operation: Operation::Square,
+                test_divisor: 1 << 40,
+                if_true: 1,
+                if_false: 1,
+            },
+            Monkey {
+                items: VecDeque::new(),
+                inspection_count: 0,
+                operation: Operation::Add(0),
+                test_divisor: 1 << 40,
+                if_true: 0,
+                if_false: 0,
+            },
+        ];
+        assert_eq!(
+            fast_forward_inspection_counts(&monkeys, 1),
+            Err("Worry modulus 1099511627776 is too large to follow in a u64".to_string())
+        );
+    }
+
+    #[test]
+    fn test_capped_worry_never_exceeds_cap() {
+        let mut monkeys = build_sample_monkeys();
+        let report = simulate(&mut monkeys, 5, &CapRelief(100), 1);
+        let max_held = report
+            .snapshots
+            .iter()
+            .flat_map(|s| s.held_items.iter().flatten())
+            .max();
+        assert_eq!(max_held, Some(&100));
     }
 }
@@ -1,5 +1,7 @@
 use std::{collections::{HashSet, VecDeque}, u32::MAX, iter};
 
+use crate::grid::{Grid, Point};
+
 pub fn day_12_pt_1(input: &str) -> u32 {
     let map = Map::from_str(input);
     return map.compute_shortest_path_length();
@@ -11,66 +13,47 @@ pub fn day_12_pt_2(input: &str) -> u32 {
 }
 
 struct Map {
-    grid: Vec<Vec<char>>,
-    start_point: Pos,
-    signal_point: Pos,
+    grid: Grid<char>,
+    start_point: Point,
+    signal_point: Point,
 }
 
 impl Map {
     fn from_str(text: &str) -> Self {
-        let grid: Vec<Vec<char>> = text.trim()
-            .lines()
-            .map(str::trim)
-            .map(|l| l.chars().collect())
-            .collect();
+        let grid = Grid::parse(text).unwrap();
         
-        let start_point = Self::find_chars_in_grid(&grid, 'S').first().unwrap().clone();
-        let signal_point = Self::find_chars_in_grid(&grid, 'E').first().unwrap().clone();
+        let start_point = grid.find(|c| *c == 'S').unwrap();
+        let signal_point = grid.find(|c| *c == 'E').unwrap();
         let translate_char = |c: &char| match c {
             'S' => 'a',
             'E' => 'z',
             _ => c.to_owned(),
         };
-        let translated_grid: Vec<Vec<char>> = grid.iter()
-            .map(|row| row.iter().map(translate_char).collect())
-            .collect();
 
         return Self {
-            grid: translated_grid,
+            grid: grid.map(translate_char),
             start_point,
             signal_point,
         };
     }
 
-    fn find_chars_in_grid(grid: &Vec<Vec<char>>, needle_char: char) -> Vec<Pos> {
-        let mut found: Vec<Pos> = Vec::new();
-        for y in 0..grid.len() {
-            for x in 0..grid[y].len() {
-                if grid[y][x] == needle_char {
-                    found.push(Pos::new(x, y));
-                }
-            }
-        }
-        return found;
-    }
-
     fn compute_shortest_path_length(&self) -> u32 {
-        // let visited_with_me: HashSet<Pos> = HashSet::from([self.start_point]);
+        // let visited_with_me: HashSet<Point> = HashSet::from([self.start_point]);
         // return self.compute_shortest_next_path(self.start_point, &visited_with_me);
         return self.breadth_first_shortest_path(self.start_point).unwrap();
     }
 
     fn compute_shortest_from_any_low_point(&self) -> u32 {
-        return Self::find_chars_in_grid(&self.grid, 'a')
-            .iter()
-            .filter_map(|start| self.breadth_first_shortest_path(*start))
+        return self.grid.iter()
+            .filter(|(_, c)| **c == 'a')
+            .filter_map(|(start, _)| self.breadth_first_shortest_path(start))
             .min()
             .unwrap();
     }
 
-    fn breadth_first_shortest_path(&self, start_point: Pos) -> Option<u32> {
-        let mut visited = vec![vec![false; self.grid[0].len()]; self.grid.len()];
-        let mut to_visit: VecDeque<(Pos, u32)> = VecDeque::new();
+    fn breadth_first_shortest_path(&self, start_point: Point) -> Option<u32> {
+        let mut visited = Grid::new(self.grid.width(), self.grid.height(), false);
+        let mut to_visit: VecDeque<(Point, u32)> = VecDeque::new();
         to_visit.push_back((start_point, 0));
 
         while let Some((cur_pos, dist)) = to_visit.pop_front() {
@@ -79,15 +62,12 @@ impl Map {
             }
 
             let my_height = self.height(&cur_pos);
-            let next_points: Vec<Pos> = cur_pos.neighbors()
-                .iter()
-                .filter(|p| self.in_bounds(p))
+            let next_points: Vec<Point> = self.grid.neighbors4(cur_pos)
                 .filter(|p| self.height(&p) <= my_height + 1)
-                .filter(|p| !visited[p.y][p.x])
-                .cloned()
+                .filter(|p| !visited[*p])
                 .collect();
             for p in next_points {
-                visited[p.y][p.x] = true;
+                visited[p] = true;
                 to_visit.push_back((p, dist+1));
             }
         }
@@ -95,59 +75,22 @@ impl Map {
         return None;
     }
 
-    fn compute_shortest_next_path(&self, cur_pos: Pos, visited: &HashSet<Pos>) -> u32 {
+    fn compute_shortest_next_path(&self, cur_pos: Point, visited: &HashSet<Point>) -> u32 {
         if cur_pos == self.signal_point {
             return visited.len() as u32;
         }
 
-        let visited_with_me: HashSet<Pos> = HashSet::from_iter(visited.iter().cloned().chain(iter::once(cur_pos))); 
+        let visited_with_me: HashSet<Point> = HashSet::from_iter(visited.iter().cloned().chain(iter::once(cur_pos))); 
         let my_height = self.height(&cur_pos);
-        return cur_pos.neighbors()
-            .iter()
-            .cloned()
-            .filter(|p| self.in_bounds(p))
+        return self.grid.neighbors4(cur_pos)
             .filter(|p| self.height(&p) <= my_height + 1)
             .filter(|p| !visited_with_me.contains(p))
             .map(|p| self.compute_shortest_next_path(p, &visited_with_me))
             .min().unwrap_or(MAX);
     }
 
-    fn in_bounds(&self, point: &Pos) -> bool {
-        return point.x < self.grid[0].len()
-            && point.y < self.grid.len();
-    }
-
-    fn height(&self, pos: &Pos) -> u32 {
-        return self.grid[pos.y][pos.x].to_digit(36).unwrap();
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
-struct Pos {
-    x: usize,
-    y: usize,
-}
-
-impl Pos {
-    fn new(x: usize, y: usize) -> Self {
-        return Self {
-            x,
-            y
-        }
-    }
-
-    fn neighbors(&self) -> Vec<Pos> {
-        let mut neighbors = vec![
-            Pos::new(self.x, self.y+1),
-            Pos::new(self.x+1, self.y),
-        ];
-        if self.y > 0 {
-            neighbors.push(Pos::new(self.x, self.y-1));
-        }
-        if self.x > 0 {
-            neighbors.push(Pos::new(self.x-1, self.y));
-        }
-        return neighbors;
+    fn height(&self, pos: &Point) -> u32 {
+        return self.grid[*pos].to_digit(36).unwrap();
     }
 }
 
@@ -165,15 +108,15 @@ mod tests {
             abdefghi
         "#;
         let map = Map::from_str(&input);
-        assert_eq!(map.grid, vec![
+        assert_eq!(map.grid, Grid::from_rows(vec![
             vec!['a', 'a', 'b', 'q', 'p', 'o', 'n', 'm'],
             vec!['a', 'b', 'c', 'r', 'y', 'x', 'x', 'l'],
             vec!['a', 'c', 'c', 's', 'z', 'z', 'x', 'k'],
             vec!['a', 'c', 'c', 't', 'u', 'v', 'w', 'j'],
             vec!['a', 'b', 'd', 'e', 'f', 'g', 'h', 'i']
-        ]);
-        assert_eq!(map.start_point, Pos::new(0, 0));
-        assert_eq!(map.signal_point, Pos::new(5, 2));
+        ]).unwrap());
+        assert_eq!(map.start_point, Point::new(0, 0));
+        assert_eq!(map.signal_point, Point::new(5, 2));
     }
 
     #[test]
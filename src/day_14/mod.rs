@@ -0,0 +1,273 @@
+use std::fmt;
+
+use crate::grid::{Grid, Point};
+
+const SAND_SOURCE: Point = Point::new(500, 0);
+
+pub fn count_resting_sand(input: &str) -> Result<usize, String> {
+    let mut cave = Cave::parse(input, false)?;
+    return Ok(cave.fill());
+}
+
+pub fn count_sand_until_source_blocked(input: &str) -> Result<usize, String> {
+    let mut cave = Cave::parse(input, true)?;
+    return Ok(cave.fill());
+}
+
+// The cave after all the sand has come to rest.
+pub fn render_filled_cave(input: &str, with_floor: bool) -> Result<String, String> {
+    let mut cave = Cave::parse(input, with_floor)?;
+    cave.fill();
+    return Ok(cave.to_string());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
+
+struct Cave {
+    tiles: Grid<Tile>,
+    // Cave x of the grid's first column.
+    min_x: i64,
+    lowest_rock: i64,
+    // The floor, if any, sits two below the lowest rock.
+    has_floor: bool,
+}
+
+impl Cave {
+    fn parse(input: &str, has_floor: bool) -> Result<Self, String> {
+        let paths = input
+            .trim()
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+            .map(|(i, line)| parse_path(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+            .collect::<Result<Vec<Vec<Point>>, String>>()?;
+        let corners = paths.iter().flatten();
+        let lowest_rock = corners.clone().map(|p| p.y).max().ok_or("No rock paths")?;
+
+        // Sand can't spread further sideways than it falls, so this fits the
+        // floor case too.
+        let reach = lowest_rock + 2;
+        let min_x = corners
+            .clone()
+            .map(|p| p.x)
+            .min()
+            .unwrap()
+            .min(SAND_SOURCE.x - reach)
+            - 1;
+        let max_x = corners
+            .map(|p| p.x)
+            .max()
+            .unwrap()
+            .max(SAND_SOURCE.x + reach)
+            + 1;
+        let tiles = Grid::new(
+            (max_x - min_x + 1) as usize,
+            (reach + 1) as usize,
+            Tile::Air,
+        );
+        let mut cave = Self {
+            tiles,
+            min_x,
+            lowest_rock,
+            has_floor,
+        };
+
+        for path in paths {
+            for segment in path.windows(2) {
+                let step = (segment[1] - segment[0]).signum();
+                let mut p = segment[0];
+                cave.set(p, Tile::Rock);
+                while p != segment[1] {
+                    p = p + step;
+                    cave.set(p, Tile::Rock);
+                }
+            }
+        }
+        return Ok(cave);
+    }
+
+    // Drops sand until a unit falls into the abyss or the source is covered,
+    // returning how many units came to rest.
+    fn fill(&mut self) -> usize {
+        let mut count = 0;
+        while self.drop_sand().is_some() {
+            count += 1;
+        }
+        return count;
+    }
+
+    // Where the next unit of sand comes to rest, if it does.
+    fn drop_sand(&mut self) -> Option<Point> {
+        if self.tile(SAND_SOURCE) != Tile::Air {
+            return None;
+        }
+        let mut sand = SAND_SOURCE;
+        'falling: loop {
+            if !self.has_floor && sand.y > self.lowest_rock {
+                return None;
+            }
+            for dx in [0, -1, 1] {
+                let next = sand + Point::new(dx, 1);
+                if self.tile(next) == Tile::Air {
+                    sand = next;
+                    continue 'falling;
+                }
+            }
+            self.set(sand, Tile::Sand);
+            return Some(sand);
+        }
+    }
+
+    fn floor(&self) -> i64 {
+        return self.lowest_rock + 2;
+    }
+
+    fn tile(&self, p: Point) -> Tile {
+        if self.has_floor && p.y == self.floor() {
+            return Tile::Rock;
+        }
+        return *self.tiles.get(self.to_grid(p)).unwrap_or(&Tile::Air);
+    }
+
+    fn set(&mut self, p: Point, tile: Tile) {
+        let grid_point = self.to_grid(p);
+        self.tiles[grid_point] = tile;
+    }
+
+    fn to_grid(&self, p: Point) -> Point {
+        return Point::new(p.x - self.min_x, p.y);
+    }
+}
+
+// Draws the box around the source, rock and sand, plus the floor if any.
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filled: Vec<Point> = self
+            .tiles
+            .iter()
+            .filter(|(_, t)| **t != Tile::Air)
+            .map(|(p, _)| p + Point::new(self.min_x, 0))
+            .chain([SAND_SOURCE])
+            .collect();
+        let min_x = filled.iter().map(|p| p.x).min().unwrap();
+        let max_x = filled.iter().map(|p| p.x).max().unwrap();
+        let max_y = if self.has_floor {
+            self.floor()
+        } else {
+            self.lowest_rock
+        };
+        for y in 0..=max_y {
+            for x in min_x..=max_x {
+                let p = Point::new(x, y);
+                let c = match self.tile(p) {
+                    Tile::Rock => '#',
+                    Tile::Sand => 'o',
+                    Tile::Air if p == SAND_SOURCE => '+',
+                    Tile::Air => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+// "498,4 -> 498,6 -> 496,6"
+fn parse_path(line: &str) -> Result<Vec<Point>, String> {
+    let points = line
+        .split("->")
+        .map(|corner| {
+            let (x, y) = corner
+                .trim()
+                .split_once(',')
+                .ok_or(format!("Not a point: {:?}", corner.trim()))?;
+            let coord = |s: &str| {
+                s.parse::<i64>()
+                    .map_err(|_| format!("Bad coordinate: {:?}", s))
+            };
+            let p = Point::new(coord(x)?, coord(y)?);
+            if p.y < 0 {
+                return Err(format!("Rock above the source at {}", p));
+            }
+            return Ok(p);
+        })
+        .collect::<Result<Vec<Point>, String>>()?;
+    for segment in points.windows(2) {
+        if segment[0].x != segment[1].x && segment[0].y != segment[1].y {
+            return Err(format!(
+                "Diagonal path from {} to {}",
+                segment[0], segment[1]
+            ));
+        }
+    }
+    return Ok(points);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        498,4 -> 498,6 -> 496,6
+        503,4 -> 502,4 -> 502,9 -> 494,9
+    "#;
+
+    #[test]
+    fn test_count_resting_sand() {
+        assert_eq!(count_resting_sand(SAMPLE), Ok(24));
+    }
+
+    #[test]
+    fn test_count_sand_until_source_blocked() {
+        assert_eq!(count_sand_until_source_blocked(SAMPLE), Ok(93));
+    }
+
+    #[test]
+    fn test_first_units_of_sand() {
+        let mut cave = Cave::parse(SAMPLE, false).unwrap();
+        assert_eq!(cave.drop_sand(), Some(Point::new(500, 8)));
+        assert_eq!(cave.drop_sand(), Some(Point::new(499, 8)));
+        assert_eq!(cave.drop_sand(), Some(Point::new(501, 8)));
+    }
+
+    #[test]
+    fn test_render_filled_cave() {
+        let expected = r#"
+......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+"#;
+        assert_eq!(
+            render_filled_cave(SAMPLE, false),
+            Ok(expected.trim_start().to_string())
+        );
+        let with_floor = render_filled_cave(SAMPLE, true).unwrap();
+        assert_eq!(with_floor.lines().next(), Some("..........o.........."));
+        assert_eq!(with_floor.lines().last(), Some("#####################"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(count_resting_sand("498,4 -> 497,5")
+            .unwrap_err()
+            .contains("Diagonal"));
+        assert!(count_resting_sand("498,4 -> 4")
+            .unwrap_err()
+            .starts_with("Line 1"));
+        assert!(count_resting_sand("").is_err());
+    }
+}
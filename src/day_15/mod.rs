@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+
+use crate::grid::Point;
+
+// Positions on `row` that can't hold a beacon.
+pub fn count_excluded_positions(input: &str, row: i64) -> Result<usize, String> {
+    let sensors = parse_sensors(input)?;
+    let covered: i64 = coverage_on_row(&sensors, row)
+        .iter()
+        .map(|(start, end)| end - start + 1)
+        .sum();
+    let beacons_on_row: HashSet<Point> = sensors
+        .iter()
+        .map(|s| s.beacon)
+        .filter(|b| b.y == row)
+        .collect();
+    return Ok(covered as usize - beacons_on_row.len());
+}
+
+pub fn calc_tuning_frequency(input: &str, bound: i64) -> Result<i64, String> {
+    let beacon = find_distress_beacon(input, bound)?
+        .ok_or(format!("Every position up to {} is covered", bound))?;
+    return Ok(beacon.x * 4_000_000 + beacon.y);
+}
+
+// The one position with 0 <= x, y <= bound that no sensor covers. Each row's
+// coverage is merged into intervals so a gap shows up without visiting cells.
+pub fn find_distress_beacon(input: &str, bound: i64) -> Result<Option<Point>, String> {
+    let sensors = parse_sensors(input)?;
+    for row in 0..=bound {
+        let mut x = 0;
+        for (start, end) in coverage_on_row(&sensors, row) {
+            if start > x {
+                break;
+            }
+            x = x.max(end + 1);
+        }
+        if x <= bound {
+            return Ok(Some(Point::new(x, row)));
+        }
+    }
+    return Ok(None);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Sensor {
+    position: Point,
+    beacon: Point,
+}
+
+impl Sensor {
+    fn radius(&self) -> i64 {
+        return self.position.manhattan_distance(self.beacon);
+    }
+
+    // Inclusive x range this sensor rules out on `row`.
+    fn covered_on_row(&self, row: i64) -> Option<(i64, i64)> {
+        let half_width = self.radius() - (self.position.y - row).abs();
+        if half_width < 0 {
+            return None;
+        }
+        return Some((self.position.x - half_width, self.position.x + half_width));
+    }
+}
+
+// Sorted, non-overlapping inclusive intervals covered on `row`.
+fn coverage_on_row(sensors: &[Sensor], row: i64) -> Vec<(i64, i64)> {
+    let mut intervals: Vec<(i64, i64)> = sensors
+        .iter()
+        .filter_map(|s| s.covered_on_row(row))
+        .collect();
+    intervals.sort_unstable();
+
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    return merged;
+}
+
+fn parse_sensors(input: &str) -> Result<Vec<Sensor>, String> {
+    return input
+        .trim()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            parse_sensor(line).ok_or(format!("Line {} is not a sensor: {:?}", i + 1, line))
+        })
+        .collect();
+}
+
+// "Sensor at x=2, y=18: closest beacon is at x=-2, y=15"
+fn parse_sensor(line: &str) -> Option<Sensor> {
+    let (sensor, beacon) = line
+        .strip_prefix("Sensor at ")?
+        .split_once(": closest beacon is at ")?;
+    return Some(Sensor {
+        position: parse_point(sensor)?,
+        beacon: parse_point(beacon)?,
+    });
+}
+
+fn parse_point(text: &str) -> Option<Point> {
+    let (x, y) = text.split_once(", ")?;
+    return Some(Point::new(
+        x.strip_prefix("x=")?.parse().ok()?,
+        y.strip_prefix("y=")?.parse().ok()?,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = r#"
+        Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+        Sensor at x=9, y=16: closest beacon is at x=10, y=16
+        Sensor at x=13, y=2: closest beacon is at x=15, y=3
+        Sensor at x=12, y=14: closest beacon is at x=10, y=16
+        Sensor at x=10, y=20: closest beacon is at x=10, y=16
+        Sensor at x=14, y=17: closest beacon is at x=10, y=16
+        Sensor at x=8, y=7: closest beacon is at x=2, y=10
+        Sensor at x=2, y=0: closest beacon is at x=2, y=10
+        Sensor at x=0, y=11: closest beacon is at x=2, y=10
+        Sensor at x=20, y=14: closest beacon is at x=25, y=17
+        Sensor at x=17, y=20: closest beacon is at x=21, y=22
+        Sensor at x=16, y=7: closest beacon is at x=15, y=3
+        Sensor at x=14, y=3: closest beacon is at x=15, y=3
+        Sensor at x=20, y=1: closest beacon is at x=15, y=3
+    "#;
+
+    #[test_case(9, 25)]
+    #[test_case(10, 26)]
+    #[test_case(11, 28)]
+    fn test_count_excluded_positions(row: i64, expected: usize) {
+        assert_eq!(count_excluded_positions(SAMPLE, row), Ok(expected));
+    }
+
+    #[test]
+    fn test_find_distress_beacon() {
+        assert_eq!(
+            find_distress_beacon(SAMPLE, 20),
+            Ok(Some(Point::new(14, 11)))
+        );
+        assert_eq!(calc_tuning_frequency(SAMPLE, 20), Ok(56000011));
+        assert_eq!(find_distress_beacon(SAMPLE, 10), Ok(None));
+    }
+
+    #[test]
+    fn test_sensor_coverage() {
+        let sensor = parse_sensor("Sensor at x=8, y=7: closest beacon is at x=2, y=10").unwrap();
+        assert_eq!(sensor.radius(), 9);
+        assert_eq!(sensor.covered_on_row(7), Some((-1, 17)));
+        assert_eq!(sensor.covered_on_row(16), Some((8, 8)));
+        assert_eq!(sensor.covered_on_row(17), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = count_excluded_positions("Sensor at x=1, y=2", 0).unwrap_err();
+        assert!(err.starts_with("Line 1"), "{}", err);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{HashMap, VecDeque};
+
+const START_VALVE: &str = "AA";
+// The search keeps a best score for every set of opened valves, so this
+// many valves already needs 2^20 entries.
+const MAX_FLOW_VALVES: usize = 20;
+
+pub fn max_pressure_alone(input: &str) -> Result<u32, String> {
+    let network = ValveNetwork::parse(input)?;
+    return Ok(network.best_by_opened(30).into_iter().max().unwrap_or(0));
+}
+
+// You and the elephant each open a separate set of valves, so the answer is
+// the best pair of disjoint sets.
+pub fn max_pressure_with_elephant(input: &str) -> Result<u32, String> {
+    let network = ValveNetwork::parse(input)?;
+    let mut best = network.best_by_opened(26);
+    let valve_count = network.flows.len();
+
+    // Turn "best with exactly these open" into "best with some subset of
+    // these open", one valve at a time.
+    for valve in 0..valve_count {
+        for mask in 0..best.len() {
+            if mask & (1 << valve) != 0 {
+                best[mask] = best[mask].max(best[mask ^ (1 << valve)]);
+            }
+        }
+    }
+    let all = best.len() - 1;
+    return Ok((0..best.len())
+        .map(|mine| best[mine] + best[all ^ mine])
+        .max()
+        .unwrap_or(0));
+}
+
+// Only the valves worth opening, plus the start, with the shortest walk
+// between every pair of them.
+struct ValveNetwork {
+    flows: Vec<u32>,
+    // Indexed like `flows`, with the start valve last.
+    distances: Vec<Vec<u32>>,
+}
+
+impl ValveNetwork {
+    fn parse(input: &str) -> Result<Self, String> {
+        let valves = input
+            .trim()
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                parse_valve(line).ok_or(format!("Line {} is not a valve: {:?}", i + 1, line))
+            })
+            .collect::<Result<Vec<Valve>, String>>()?;
+
+        let index: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let tunnels = valves
+            .iter()
+            .map(|v| {
+                v.tunnels
+                    .iter()
+                    .map(|t| {
+                        index
+                            .get(t.as_str())
+                            .cloned()
+                            .ok_or(format!("{} leads to unknown valve {}", v.name, t))
+                    })
+                    .collect::<Result<Vec<usize>, String>>()
+            })
+            .collect::<Result<Vec<Vec<usize>>, String>>()?;
+        let start = *index
+            .get(START_VALVE)
+            .ok_or(format!("No valve {}", START_VALVE))?;
+
+        let mut kept: Vec<usize> = (0..valves.len()).filter(|i| valves[*i].flow > 0).collect();
+        if kept.len() > MAX_FLOW_VALVES {
+            return Err(format!(
+                "{} valves have flow, but at most {} can be searched",
+                kept.len(),
+                MAX_FLOW_VALVES
+            ));
+        }
+        kept.push(start);
+
+        let distances = kept
+            .iter()
+            .map(|from| {
+                let walk = walking_distances(&tunnels, *from);
+                return kept.iter().map(|to| walk[*to]).collect();
+            })
+            .collect();
+        return Ok(Self {
+            flows: kept[..kept.len() - 1]
+                .iter()
+                .map(|i| valves[*i].flow)
+                .collect(),
+            distances,
+        });
+    }
+
+    // Most pressure released in `minutes` for every set of opened valves,
+    // indexed by bitmask. Sets that can't be opened in time stay at 0.
+    fn best_by_opened(&self, minutes: u32) -> Vec<u32> {
+        let mut best = vec![0; 1 << self.flows.len()];
+        self.explore(self.flows.len(), minutes, 0, 0, &mut best);
+        return best;
+    }
+
+    fn explore(
+        &self,
+        at: usize,
+        time_left: u32,
+        opened: usize,
+        released: u32,
+        best: &mut Vec<u32>,
+    ) {
+        best[opened] = best[opened].max(released);
+        for next in 0..self.flows.len() {
+            // Walking there plus a minute to turn the valve.
+            let cost = self.distances[at][next].saturating_add(1);
+            if opened & (1 << next) != 0 || cost >= time_left {
+                continue;
+            }
+            let remaining = time_left - cost;
+            self.explore(
+                next,
+                remaining,
+                opened | (1 << next),
+                released + remaining * self.flows[next],
+                best,
+            );
+        }
+    }
+}
+
+// Breadth first over the full tunnel graph; unreachable valves get u32::MAX.
+fn walking_distances(tunnels: &[Vec<usize>], from: usize) -> Vec<u32> {
+    let mut distances = vec![u32::MAX; tunnels.len()];
+    distances[from] = 0;
+    let mut to_visit = VecDeque::from([from]);
+    while let Some(valve) = to_visit.pop_front() {
+        for next in &tunnels[valve] {
+            if distances[*next] == u32::MAX {
+                distances[*next] = distances[valve] + 1;
+                to_visit.push_back(*next);
+            }
+        }
+    }
+    return distances;
+}
+
+struct Valve {
+    name: String,
+    flow: u32,
+    tunnels: Vec<String>,
+}
+
+// "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB"
+fn parse_valve(line: &str) -> Option<Valve> {
+    let (valve, tunnels) = line.split_once("; ")?;
+    let (name, flow) = valve
+        .strip_prefix("Valve ")?
+        .split_once(" has flow rate=")?;
+    let tunnels = tunnels
+        .strip_prefix("tunnels lead to valves ")
+        .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))?;
+    return Some(Valve {
+        name: name.to_string(),
+        flow: flow.parse().ok()?,
+        tunnels: tunnels.split(", ").map(str::to_string).collect(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+        Valve BB has flow rate=13; tunnels lead to valves CC, AA
+        Valve CC has flow rate=2; tunnels lead to valves DD, BB
+        Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+        Valve EE has flow rate=3; tunnels lead to valves FF, DD
+        Valve FF has flow rate=0; tunnels lead to valves EE, GG
+        Valve GG has flow rate=0; tunnels lead to valves FF, HH
+        Valve HH has flow rate=22; tunnel leads to valve GG
+        Valve II has flow rate=0; tunnels lead to valves AA, JJ
+        Valve JJ has flow rate=21; tunnel leads to valve II
+    "#;
+
+    #[test]
+    fn test_max_pressure_alone() {
+        assert_eq!(max_pressure_alone(SAMPLE), Ok(1651));
+    }
+
+    #[test]
+    fn test_max_pressure_with_elephant() {
+        assert_eq!(max_pressure_with_elephant(SAMPLE), Ok(1707));
+    }
+
+    #[test]
+    fn test_compressed_network() {
+        let network = ValveNetwork::parse(SAMPLE).unwrap();
+        assert_eq!(network.flows, vec![13, 2, 20, 3, 22, 21]);
+        // From AA: BB is one step, HH is five.
+        assert_eq!(network.distances[6][0], 1);
+        assert_eq!(network.distances[6][4], 5);
+        assert_eq!(network.distances[4][5], 7);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let unknown = "Valve AA has flow rate=0; tunnel leads to valve ZZ";
+        assert!(max_pressure_alone(unknown)
+            .unwrap_err()
+            .contains("unknown valve ZZ"));
+        let no_start = "Valve BB has flow rate=1; tunnel leads to valve BB";
+        assert!(max_pressure_alone(no_start)
+            .unwrap_err()
+            .contains("No valve AA"));
+        assert!(max_pressure_alone("Valve AA")
+            .unwrap_err()
+            .starts_with("Line 1"));
+    }
+
+    #[test]
+    fn test_too_many_flow_valves() {
+        // AA leads to 21 valves with flow, each leading back.
+        let names: Vec<String> = (0..21).map(|i| format!("V{}", i)).collect();
+        let mut input = format!(
+            "Valve AA has flow rate=0; tunnels lead to valves {}\n",
+            names.join(", ")
+        );
+        for name in &names {
+            input += &format!("Valve {} has flow rate=1; tunnel leads to valve AA\n", name);
+        }
+        assert_eq!(
+            max_pressure_alone(&input),
+            Err("21 valves have flow, but at most 20 can be searched".to_string())
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+const CHAMBER_WIDTH: usize = 7;
+
+// Rows from the bottom up, bit x set for column x, already two in from the
+// left wall where each rock appears.
+const ROCKS: [&[u8]; 5] = [
+    &[0b0111100],
+    &[0b0001000, 0b0011100, 0b0001000],
+    &[0b0011100, 0b0010000, 0b0010000],
+    &[0b0000100, 0b0000100, 0b0000100, 0b0000100],
+    &[0b0001100, 0b0001100],
+];
+
+// Rows from the top of the tower that make up the state for cycle detection.
+const SURFACE_ROWS: usize = 32;
+
+pub fn tower_height(input: &str, rocks: u64) -> Result<u64, String> {
+    let mut chamber = Chamber::new(parse_jets(input)?);
+    let mut seen: HashMap<(usize, usize, [u8; SURFACE_ROWS]), (u64, u64)> = HashMap::new();
+    let mut skipped_height = 0;
+    let mut dropped = 0;
+
+    while dropped < rocks {
+        chamber.drop_rock();
+        dropped += 1;
+        if skipped_height > 0 || chamber.rows.len() < SURFACE_ROWS {
+            continue;
+        }
+        let key = (chamber.rock_index, chamber.jet_index, chamber.surface());
+        let height = chamber.rows.len() as u64;
+        if let Some((cycle_start, start_height)) = seen.insert(key, (dropped, height)) {
+            // The same rock and jet land on the same surface: everything from
+            // here repeats, so skip whole cycles at once.
+            let cycle_rocks = dropped - cycle_start;
+            let cycles = (rocks - dropped) / cycle_rocks;
+            skipped_height = cycles * (height - start_height);
+            dropped += cycles * cycle_rocks;
+        }
+    }
+    return Ok(chamber.rows.len() as u64 + skipped_height);
+}
+
+// Top `rows` rows of the chamber after `rocks` rocks, drawn like the puzzle.
+pub fn render_chamber_top(input: &str, rocks: usize, rows: usize) -> Result<String, String> {
+    let mut chamber = Chamber::new(parse_jets(input)?);
+    for _ in 0..rocks {
+        chamber.drop_rock();
+    }
+    let mut rendered = String::new();
+    for row in chamber.rows.iter().rev().take(rows) {
+        rendered.push('|');
+        for x in 0..CHAMBER_WIDTH {
+            rendered.push(if row & (1 << x) != 0 { '#' } else { '.' });
+        }
+        rendered.push_str("|\n");
+    }
+    if rows > chamber.rows.len() {
+        rendered.push_str("+-------+\n");
+    }
+    return Ok(rendered);
+}
+
+struct Chamber {
+    // Settled rock, bottom row first.
+    rows: Vec<u8>,
+    jets: Vec<i8>,
+    jet_index: usize,
+    rock_index: usize,
+}
+
+impl Chamber {
+    fn new(jets: Vec<i8>) -> Self {
+        return Self {
+            rows: Vec::new(),
+            jets,
+            jet_index: 0,
+            rock_index: 0,
+        };
+    }
+
+    fn drop_rock(&mut self) {
+        let mut rock = ROCKS[self.rock_index].to_vec();
+        self.rock_index = (self.rock_index + 1) % ROCKS.len();
+        let mut bottom = self.rows.len() + 3;
+
+        loop {
+            let jet = self.jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.jets.len();
+            let pushed: Option<Vec<u8>> = if jet < 0 {
+                (rock.iter().all(|r| r & 1 == 0)).then(|| rock.iter().map(|r| r >> 1).collect())
+            } else {
+                (rock.iter().all(|r| r & (1 << (CHAMBER_WIDTH - 1)) == 0))
+                    .then(|| rock.iter().map(|r| r << 1).collect())
+            };
+            if let Some(pushed) = pushed {
+                if !self.collides(&pushed, bottom) {
+                    rock = pushed;
+                }
+            }
+
+            if bottom == 0 || self.collides(&rock, bottom - 1) {
+                break;
+            }
+            bottom -= 1;
+        }
+
+        for (i, r) in rock.iter().enumerate() {
+            if bottom + i == self.rows.len() {
+                self.rows.push(0);
+            }
+            self.rows[bottom + i] |= r;
+        }
+    }
+
+    fn collides(&self, rock: &[u8], bottom: usize) -> bool {
+        return rock
+            .iter()
+            .enumerate()
+            .any(|(i, r)| self.rows.get(bottom + i).is_some_and(|row| row & r != 0));
+    }
+
+    fn surface(&self) -> [u8; SURFACE_ROWS] {
+        let mut surface = [0; SURFACE_ROWS];
+        for (i, row) in self.rows.iter().rev().take(SURFACE_ROWS).enumerate() {
+            surface[i] = *row;
+        }
+        return surface;
+    }
+}
+
+fn parse_jets(input: &str) -> Result<Vec<i8>, String> {
+    let jets = input
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '<' => Ok(-1),
+            '>' => Ok(1),
+            _ => Err(format!("Not a jet: {:?}", c)),
+        })
+        .collect::<Result<Vec<i8>, String>>()?;
+    if jets.is_empty() {
+        return Err("No jets".to_string());
+    }
+    return Ok(jets);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test_case(1, 1)]
+    #[test_case(10, 17)]
+    #[test_case(2022, 3068)]
+    #[test_case(1_000_000_000_000, 1514285714288)]
+    fn test_tower_height(rocks: u64, expected: u64) {
+        assert_eq!(tower_height(SAMPLE, rocks), Ok(expected));
+    }
+
+    #[test]
+    fn test_cycle_skip_matches_plain_simulation() {
+        let mut chamber = Chamber::new(parse_jets(SAMPLE).unwrap());
+        for rocks in 1..=500 {
+            chamber.drop_rock();
+            assert_eq!(
+                tower_height(SAMPLE, rocks),
+                Ok(chamber.rows.len() as u64),
+                "{} rocks",
+                rocks
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_chamber_top() {
+        let expected = r#"
+|...#...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+"#;
+        assert_eq!(
+            render_chamber_top(SAMPLE, 2, 10),
+            Ok(expected.trim_start().to_string())
+        );
+        assert_eq!(
+            render_chamber_top(SAMPLE, 2, 1),
+            Ok("|...#...|\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(tower_height("<>x", 1).is_err());
+        assert!(tower_height("\n", 1).is_err());
+    }
+}
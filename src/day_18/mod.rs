@@ -0,0 +1,126 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::grid::Point3;
+
+// Every cube face that isn't pressed against another cube.
+pub fn calc_surface_area(input: &str) -> Result<usize, String> {
+    let cubes = parse_cubes(input)?;
+    return Ok(cubes
+        .iter()
+        .flat_map(|c| c.neighbors6())
+        .filter(|n| !cubes.contains(n))
+        .count());
+}
+
+// Only faces the steam can reach from outside. Air pockets trapped inside
+// the droplet don't count.
+pub fn calc_exterior_surface_area(input: &str) -> Result<usize, String> {
+    let cubes = parse_cubes(input)?;
+    let Some(first) = cubes.iter().next() else {
+        return Ok(0);
+    };
+
+    // One unit of padding lets the steam flow all the way around.
+    let padding = Point3::new(1, 1, 1);
+    let low = cubes.iter().fold(*first, |acc, c| acc.min_each(*c)) - padding;
+    let high = cubes.iter().fold(*first, |acc, c| acc.max_each(*c)) + padding;
+    let in_box = |p: &Point3| {
+        p.x >= low.x
+            && p.y >= low.y
+            && p.z >= low.z
+            && p.x <= high.x
+            && p.y <= high.y
+            && p.z <= high.z
+    };
+
+    let mut steam: HashSet<Point3> = HashSet::from([low]);
+    let mut to_visit = VecDeque::from([low]);
+    let mut faces = 0;
+    while let Some(air) = to_visit.pop_front() {
+        for next in air.neighbors6() {
+            if cubes.contains(&next) {
+                faces += 1;
+            } else if in_box(&next) && steam.insert(next) {
+                to_visit.push_back(next);
+            }
+        }
+    }
+    return Ok(faces);
+}
+
+fn parse_cubes(input: &str) -> Result<HashSet<Point3>, String> {
+    return input
+        .trim()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let coords: Vec<i64> = line
+                .split(',')
+                .map(|c| c.trim().parse())
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|_| format!("Line {} is not a cube: {:?}", i + 1, line))?;
+            return match coords[..] {
+                [x, y, z] => Ok(Point3::new(x, y, z)),
+                _ => Err(format!("Line {} is not a cube: {:?}", i + 1, line)),
+            };
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = r#"
+        2,2,2
+        1,2,2
+        3,2,2
+        2,1,2
+        2,3,2
+        2,2,1
+        2,2,3
+        2,2,4
+        2,2,6
+        1,2,5
+        3,2,5
+        2,1,5
+        2,3,5
+    "#;
+
+    #[test_case("1,1,1\n2,1,1", 10)]
+    #[test_case(SAMPLE, 64)]
+    fn test_surface_area(input: &str, expected: usize) {
+        assert_eq!(calc_surface_area(input), Ok(expected));
+    }
+
+    #[test_case("1,1,1\n2,1,1", 10)]
+    #[test_case(SAMPLE, 58)]
+    fn test_exterior_surface_area(input: &str, expected: usize) {
+        assert_eq!(calc_exterior_surface_area(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_hollow_cube_hides_its_inside() {
+        let mut shell = String::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    if (x, y, z) != (1, 1, 1) {
+                        shell.push_str(&format!("{},{},{}\n", x, y, z));
+                    }
+                }
+            }
+        }
+        assert_eq!(calc_surface_area(&shell), Ok(54 + 6));
+        assert_eq!(calc_exterior_surface_area(&shell), Ok(54));
+    }
+
+    #[test_case("1,2")]
+    #[test_case("1,2,x")]
+    fn test_parse_errors(input: &str) {
+        assert!(calc_surface_area(input).unwrap_err().starts_with("Line 1"));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{fmt, thread};
+
+pub fn sum_quality_levels(input: &str) -> Result<u32, String> {
+    let blueprints = parse_blueprints(input)?;
+    let plans = plan_in_parallel(&blueprints, 24);
+    return Ok(blueprints
+        .iter()
+        .zip(plans)
+        .map(|(b, plan)| b.id * plan.geodes)
+        .sum());
+}
+
+pub fn multiply_first_three(input: &str) -> Result<u32, String> {
+    let blueprints = parse_blueprints(input)?;
+    let first = &blueprints[..blueprints.len().min(3)];
+    return Ok(plan_in_parallel(first, 32)
+        .iter()
+        .map(|p| p.geodes)
+        .product());
+}
+
+pub fn best_build_order(input: &str, blueprint_id: u32, minutes: u32) -> Result<BuildPlan, String> {
+    let blueprints = parse_blueprints(input)?;
+    let blueprint = blueprints
+        .iter()
+        .find(|b| b.id == blueprint_id)
+        .ok_or(format!("No blueprint {}", blueprint_id))?;
+    return Ok(blueprint.best_plan(minutes));
+}
+
+// Each blueprint is searched on its own thread.
+fn plan_in_parallel(blueprints: &[Blueprint], minutes: u32) -> Vec<BuildPlan> {
+    return thread::scope(|scope| {
+        let handles: Vec<_> = blueprints
+            .iter()
+            .map(|b| scope.spawn(move || b.best_plan(minutes)))
+            .collect();
+        return handles.into_iter().map(|h| h.join().unwrap()).collect();
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Robot {
+    Ore,
+    Clay,
+    Obsidian,
+    Geode,
+}
+
+const ROBOTS: [Robot; 4] = [Robot::Ore, Robot::Clay, Robot::Obsidian, Robot::Geode];
+
+impl fmt::Display for Robot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Robot::Ore => "ore",
+            Robot::Clay => "clay",
+            Robot::Obsidian => "obsidian",
+            Robot::Geode => "geode",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BuildPlan {
+    pub geodes: u32,
+    // The minute each robot starts being built, in order.
+    pub steps: Vec<(u32, Robot)>,
+}
+
+impl fmt::Display for BuildPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (minute, robot) in &self.steps {
+            writeln!(f, "Minute {}: build {}-collecting robot", minute, robot)?;
+        }
+        write!(f, "Geodes: {}", self.geodes)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Blueprint {
+    id: u32,
+    // Ore, clay and obsidian needed for each robot, indexed like ROBOTS.
+    costs: [[u32; 3]; 4],
+}
+
+impl Blueprint {
+    fn best_plan(&self, minutes: u32) -> BuildPlan {
+        let mut search = Search {
+            blueprint: self,
+            minutes,
+            // Only one robot can be built a minute, so there's no use
+            // producing more of a material than the priciest robot needs.
+            max_useful: [0, 1, 2].map(|r| self.costs.iter().map(|c| c[r]).max().unwrap()),
+            best: BuildPlan {
+                geodes: 0,
+                steps: Vec::new(),
+            },
+            steps: Vec::new(),
+        };
+        search.explore(minutes, [0; 4], [1, 0, 0, 0]);
+        return search.best;
+    }
+}
+
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    minutes: u32,
+    max_useful: [u32; 3],
+    best: BuildPlan,
+    steps: Vec<(u32, Robot)>,
+}
+
+impl Search<'_> {
+    // Branches on which robot to build next, jumping ahead to when it can be
+    // afforded, rather than stepping minute by minute.
+    fn explore(&mut self, time_left: u32, stock: [u32; 4], robots: [u32; 4]) {
+        let geodes_if_idle = stock[3] + robots[3] * time_left;
+        if geodes_if_idle > self.best.geodes {
+            self.best = BuildPlan {
+                geodes: geodes_if_idle,
+                steps: self.steps.clone(),
+            };
+        }
+        // Even a new geode robot every remaining minute can't beat the best.
+        if geodes_if_idle + time_left * time_left.saturating_sub(1) / 2 <= self.best.geodes {
+            return;
+        }
+
+        for robot in (0..ROBOTS.len()).rev() {
+            if robot < 3
+                && robots[robot] * time_left + stock[robot] >= self.max_useful[robot] * time_left
+            {
+                continue;
+            }
+            let Some(wait) = self.minutes_until_affordable(robot, &stock, &robots) else {
+                continue;
+            };
+            let spent = wait + 1;
+            if spent >= time_left {
+                continue;
+            }
+
+            let mut next_stock = stock;
+            for r in 0..4 {
+                next_stock[r] += robots[r] * spent;
+            }
+            for (r, cost) in self.blueprint.costs[robot].iter().enumerate() {
+                next_stock[r] -= cost;
+            }
+            let mut next_robots = robots;
+            next_robots[robot] += 1;
+
+            self.steps
+                .push((self.minutes - time_left + spent, ROBOTS[robot]));
+            self.explore(time_left - spent, next_stock, next_robots);
+            self.steps.pop();
+        }
+    }
+
+    fn minutes_until_affordable(
+        &self,
+        robot: usize,
+        stock: &[u32; 4],
+        robots: &[u32; 4],
+    ) -> Option<u32> {
+        let mut wait = 0;
+        for (r, cost) in self.blueprint.costs[robot].iter().enumerate() {
+            if stock[r] >= *cost {
+                continue;
+            }
+            if robots[r] == 0 {
+                return None;
+            }
+            wait = wait.max((cost - stock[r]).div_ceil(robots[r]));
+        }
+        return Some(wait);
+    }
+}
+
+// Blueprints may wrap over several lines, so split on the keyword instead.
+fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>, String> {
+    let blueprints = input
+        .split("Blueprint")
+        .map(|text| text.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .map(|text| parse_blueprint(&text).ok_or(format!("Not a blueprint: {:?}", text)))
+        .collect::<Result<Vec<Blueprint>, String>>()?;
+    if blueprints.is_empty() {
+        return Err("No blueprints".to_string());
+    }
+    return Ok(blueprints);
+}
+
+// "1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian
+// robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian."
+fn parse_blueprint(text: &str) -> Option<Blueprint> {
+    let (id, rest) = text.split_once(':')?;
+    let mut costs = [[0; 3]; 4];
+    for sentence in rest.split('.').map(str::trim).filter(|s| !s.is_empty()) {
+        let (robot, cost_text) = sentence
+            .strip_prefix("Each ")?
+            .split_once(" robot costs ")?;
+        let robot = ["ore", "clay", "obsidian", "geode"]
+            .iter()
+            .position(|r| *r == robot)?;
+        for cost in cost_text.split(" and ") {
+            let (amount, material) = cost.split_once(' ')?;
+            let material = ["ore", "clay", "obsidian"]
+                .iter()
+                .position(|m| *m == material)?;
+            costs[robot][material] = amount.parse().ok()?;
+        }
+    }
+    return Some(Blueprint {
+        id: id.trim().parse().ok()?,
+        costs,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        Blueprint 1:
+          Each ore robot costs 4 ore.
+          Each clay robot costs 2 ore.
+          Each obsidian robot costs 3 ore and 14 clay.
+          Each geode robot costs 2 ore and 7 obsidian.
+
+        Blueprint 2:
+          Each ore robot costs 2 ore.
+          Each clay robot costs 3 ore.
+          Each obsidian robot costs 3 ore and 8 clay.
+          Each geode robot costs 3 ore and 12 obsidian.
+    "#;
+
+    // Follows a plan minute by minute, returning the geodes it opens or None
+    // if a robot is started before it can be paid for.
+    fn replay(blueprint: &Blueprint, minutes: u32, plan: &BuildPlan) -> Option<u32> {
+        let mut stock = [0u32; 4];
+        let mut robots = [1, 0, 0, 0];
+        let mut steps = plan.steps.iter().peekable();
+        for minute in 1..=minutes {
+            let building = steps
+                .next_if(|(m, _)| *m == minute)
+                .map(|(_, r)| *r as usize);
+            if let Some(robot) = building {
+                for (r, cost) in blueprint.costs[robot].iter().enumerate() {
+                    stock[r] = stock[r].checked_sub(*cost)?;
+                }
+            }
+            for r in 0..4 {
+                stock[r] += robots[r];
+            }
+            if let Some(robot) = building {
+                robots[robot] += 1;
+            }
+        }
+        return Some(stock[3]);
+    }
+
+    #[test]
+    fn test_parse_blueprints() {
+        let blueprints = parse_blueprints(SAMPLE).unwrap();
+        assert_eq!(blueprints.len(), 2);
+        assert_eq!(
+            blueprints[0],
+            Blueprint {
+                id: 1,
+                costs: [[4, 0, 0], [2, 0, 0], [3, 14, 0], [2, 0, 7]],
+            }
+        );
+        assert!(parse_blueprints("Blueprint 1: Each ore robot costs four ore.").is_err());
+    }
+
+    #[test]
+    fn test_sum_quality_levels() {
+        assert_eq!(sum_quality_levels(SAMPLE), Ok(33));
+    }
+
+    #[test]
+    fn test_multiply_first_three() {
+        assert_eq!(multiply_first_three(SAMPLE), Ok(56 * 62));
+    }
+
+    #[test]
+    fn test_best_build_order_is_playable() {
+        let blueprints = parse_blueprints(SAMPLE).unwrap();
+        for (blueprint, expected) in blueprints.iter().zip([9, 12]) {
+            let plan = best_build_order(SAMPLE, blueprint.id, 24).unwrap();
+            assert_eq!(plan.geodes, expected);
+            assert_eq!(replay(blueprint, 24, &plan), Some(expected));
+        }
+        assert!(best_build_order(SAMPLE, 3, 24).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+const DECRYPTION_KEY: i64 = 811589153;
+
+pub fn sum_grove_coordinates(input: &str) -> Result<i64, String> {
+    let numbers = parse_numbers(input)?;
+    return grove_coordinates_sum(&mix(&numbers, 1));
+}
+
+pub fn sum_decrypted_grove_coordinates(input: &str) -> Result<i64, String> {
+    let numbers: Vec<i64> = parse_numbers(input)?
+        .iter()
+        .map(|n| n * DECRYPTION_KEY)
+        .collect();
+    return grove_coordinates_sum(&mix(&numbers, 10));
+}
+
+// Moves every number, in its original order, as many places as its value.
+// The list holds indices into `numbers` rather than values so duplicates
+// stay distinct.
+fn mix(numbers: &[i64], rounds: usize) -> Vec<i64> {
+    if numbers.len() < 2 {
+        return numbers.to_vec();
+    }
+    let mut order: Vec<usize> = (0..numbers.len()).collect();
+    // A number moving around the rest of the circle passes n - 1 others.
+    let others = numbers.len() as i64 - 1;
+    for _ in 0..rounds {
+        for (original, value) in numbers.iter().enumerate() {
+            let from = order.iter().position(|i| *i == original).unwrap();
+            order.remove(from);
+            let to = (from as i64 + value).rem_euclid(others) as usize;
+            order.insert(to, original);
+        }
+    }
+    return order.iter().map(|i| numbers[*i]).collect();
+}
+
+// The 1000th, 2000th and 3000th numbers after the 0, wrapping around.
+fn grove_coordinates_sum(mixed: &[i64]) -> Result<i64, String> {
+    let zero = mixed
+        .iter()
+        .position(|n| *n == 0)
+        .ok_or("No 0 in the list")?;
+    return Ok([1000, 2000, 3000]
+        .iter()
+        .map(|offset| mixed[(zero + offset) % mixed.len()])
+        .sum());
+}
+
+fn parse_numbers(input: &str) -> Result<Vec<i64>, String> {
+    return input
+        .trim()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|_| format!("Line {} is not a number: {:?}", i + 1, line))
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = "1\n2\n-3\n3\n-2\n0\n4\n";
+
+    // Rotated so `first` comes first, since only the circular order matters.
+    // It needs to be unique in the list.
+    fn normalized(mut numbers: Vec<i64>, first: i64) -> Vec<i64> {
+        let start = numbers.iter().position(|n| *n == first).unwrap();
+        numbers.rotate_left(start);
+        return numbers;
+    }
+
+    // Moves each number one swap at a time, the way the puzzle describes it.
+    fn mix_by_swapping(numbers: &[i64]) -> Vec<i64> {
+        let len = numbers.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        for (original, value) in numbers.iter().enumerate() {
+            let mut at = order.iter().position(|i| *i == original).unwrap();
+            for _ in 0..value.unsigned_abs() {
+                let next = if *value > 0 {
+                    (at + 1) % len
+                } else {
+                    (at + len - 1) % len
+                };
+                order.swap(at, next);
+                at = next;
+            }
+        }
+        return order.iter().map(|i| numbers[*i]).collect();
+    }
+
+    #[test]
+    fn test_mix_sample() {
+        let mixed = mix(&parse_numbers(SAMPLE).unwrap(), 1);
+        assert_eq!(normalized(mixed, 1), vec![1, 2, -3, 4, 0, 3, -2]);
+    }
+
+    #[test_case(&[0, 1, 1, -1, 1])]
+    #[test_case(&[3, 0, 3, 3, -3])]
+    #[test_case(&[2, -7, 0, 2, 11, 2, -7])]
+    fn test_mix_with_duplicates_matches_swapping(numbers: &[i64]) {
+        assert_eq!(
+            normalized(mix(numbers, 1), 0),
+            normalized(mix_by_swapping(numbers), 0)
+        );
+    }
+
+    #[test]
+    fn test_sum_grove_coordinates() {
+        assert_eq!(sum_grove_coordinates(SAMPLE), Ok(3));
+    }
+
+    #[test]
+    fn test_sum_decrypted_grove_coordinates() {
+        assert_eq!(sum_decrypted_grove_coordinates(SAMPLE), Ok(1623178306));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(sum_grove_coordinates("1\n2\n").is_err());
+        assert!(sum_grove_coordinates("1\nx\n")
+            .unwrap_err()
+            .starts_with("Line 2"));
+    }
+}
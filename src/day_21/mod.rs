@@ -0,0 +1,311 @@
+use std::{collections::HashMap, fmt};
+
+use self::rational::Rational;
+
+mod rational;
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+pub fn evaluate_root(input: &str) -> Result<i128, String> {
+    let root = parse_tree(input)?;
+    let value = root.evaluate()?;
+    return value
+        .to_integer()
+        .ok_or(format!("root yells {}, not a whole number", value));
+}
+
+// The number to yell so both sides of root's operation are equal.
+pub fn solve_for_human(input: &str) -> Result<i128, String> {
+    let Expr::Operation(left, _, right) = parse_tree(input)? else {
+        return Err("root has no operation to balance".to_string());
+    };
+    let value = match (left.contains_human(), right.contains_human()) {
+        (true, false) => left.solve(right.evaluate()?)?,
+        (false, true) => right.solve(left.evaluate()?)?,
+        _ => return Err(format!("{} must appear on exactly one side of root", HUMAN)),
+    };
+    return value
+        .to_integer()
+        .ok_or(format!("{} would have to yell {}", HUMAN, value));
+}
+
+// Root's two sides as an equation, with everything not involving the
+// human worked out.
+pub fn render_equation(input: &str) -> Result<String, String> {
+    let Expr::Operation(left, _, right) = parse_tree(input)? else {
+        return Err("root has no operation to balance".to_string());
+    };
+    return Ok(format!("{} = {}", left.simplify()?, right.simplify()?));
+}
+
+// The whole tree as it was parsed, numbers and all.
+pub fn render_tree(input: &str) -> Result<String, String> {
+    return Ok(parse_tree(input)?.to_string());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn apply(&self, left: Rational, right: Rational) -> Result<Rational, String> {
+        let result = match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide if right == Rational::from_integer(0) => {
+                return Err(format!("Division by zero in {} / {}", left, right));
+            }
+            Operator::Divide => left.checked_div(right),
+        };
+        return result.ok_or(format!("{} {} {} overflows", left, self.symbol(), right));
+    }
+
+    fn symbol(&self) -> char {
+        return match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    Number(Rational),
+    // The human's monkey, holding the number it yells in part 1.
+    Human(Rational),
+    Operation(Box<Expr>, Operator, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self) -> Result<Rational, String> {
+        return match self {
+            Expr::Number(value) | Expr::Human(value) => Ok(*value),
+            Expr::Operation(left, op, right) => op.apply(left.evaluate()?, right.evaluate()?),
+        };
+    }
+
+    fn contains_human(&self) -> bool {
+        return match self {
+            Expr::Number(_) => false,
+            Expr::Human(_) => true,
+            Expr::Operation(left, _, right) => left.contains_human() || right.contains_human(),
+        };
+    }
+
+    // Walks down to the human, undoing each operation on the way so that
+    // this expression comes out as `target`.
+    fn solve(&self, target: Rational) -> Result<Rational, String> {
+        let (left, op, right) = match self {
+            Expr::Human(_) => return Ok(target),
+            Expr::Number(_) => return Err(format!("{} is not in this branch", HUMAN)),
+            Expr::Operation(left, op, right) => (left, op, right),
+        };
+        if left.contains_human() && right.contains_human() {
+            return Err(format!(
+                "{} appears on both sides of {}",
+                HUMAN,
+                op.symbol()
+            ));
+        }
+        if left.contains_human() {
+            let known = right.evaluate()?;
+            let new_target = match op {
+                Operator::Add => Operator::Subtract.apply(target, known)?,
+                Operator::Subtract => Operator::Add.apply(target, known)?,
+                Operator::Multiply => Operator::Divide.apply(target, known)?,
+                Operator::Divide => Operator::Multiply.apply(target, known)?,
+            };
+            return left.solve(new_target);
+        }
+        let known = left.evaluate()?;
+        let new_target = match op {
+            Operator::Add => Operator::Subtract.apply(target, known)?,
+            Operator::Subtract => Operator::Subtract.apply(known, target)?,
+            Operator::Multiply => Operator::Divide.apply(target, known)?,
+            Operator::Divide => Operator::Divide.apply(known, target)?,
+        };
+        return right.solve(new_target);
+    }
+
+    // Folds every branch without the human down to a number.
+    fn simplify(&self) -> Result<Expr, String> {
+        if !self.contains_human() {
+            return Ok(Expr::Number(self.evaluate()?));
+        }
+        return match self {
+            Expr::Operation(left, op, right) => Ok(Expr::Operation(
+                Box::new(left.simplify()?),
+                *op,
+                Box::new(right.simplify()?),
+            )),
+            _ => Ok(self.clone()),
+        };
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Human(_) => write!(f, "{}", HUMAN),
+            Expr::Operation(left, op, right) => write!(f, "({} {} {})", left, op.symbol(), right),
+        };
+    }
+}
+
+enum Job<'a> {
+    Yell(i128),
+    Wait(&'a str, Operator, &'a str),
+}
+
+fn parse_tree(input: &str) -> Result<Expr, String> {
+    let jobs = input
+        .trim()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            parse_job(line).ok_or(format!("Line {} is not a monkey: {:?}", i + 1, line))
+        })
+        .collect::<Result<HashMap<&str, Job>, String>>()?;
+    return build_expr(&jobs, ROOT, &mut Vec::new());
+}
+
+fn build_expr<'a>(
+    jobs: &HashMap<&'a str, Job<'a>>,
+    name: &'a str,
+    path: &mut Vec<&'a str>,
+) -> Result<Expr, String> {
+    if path.contains(&name) {
+        return Err(format!("Monkey {} waits on itself", name));
+    }
+    let job = jobs.get(name).ok_or(format!("No monkey named {}", name))?;
+    return match job {
+        Job::Yell(value) if name == HUMAN => Ok(Expr::Human(Rational::from_integer(*value))),
+        Job::Yell(value) => Ok(Expr::Number(Rational::from_integer(*value))),
+        Job::Wait(left, op, right) => {
+            path.push(name);
+            let expr = Expr::Operation(
+                Box::new(build_expr(jobs, left, path)?),
+                *op,
+                Box::new(build_expr(jobs, right, path)?),
+            );
+            path.pop();
+            Ok(expr)
+        }
+    };
+}
+
+// "root: pppw + sjmn" or "dbpl: 5"
+fn parse_job(line: &str) -> Option<(&str, Job<'_>)> {
+    let (name, job) = line.split_once(": ")?;
+    if let Ok(value) = job.parse() {
+        return Some((name, Job::Yell(value)));
+    }
+    let parts: Vec<&str> = job.split_whitespace().collect();
+    let [left, op, right] = parts[..] else {
+        return None;
+    };
+    let op = match op {
+        "+" => Operator::Add,
+        "-" => Operator::Subtract,
+        "*" => Operator::Multiply,
+        "/" => Operator::Divide,
+        _ => return None,
+    };
+    return Some((name, Job::Wait(left, op, right)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = r#"
+        root: pppw + sjmn
+        dbpl: 5
+        cczh: sllz + lgvd
+        zczc: 2
+        ptdq: humn - dvpt
+        dvpt: 3
+        lfqf: 4
+        humn: 5
+        ljgn: 2
+        sjmn: drzm * dbpl
+        sllz: 4
+        pppw: cczh / lfqf
+        lgvd: ljgn * ptdq
+        drzm: hmdt - zczc
+        hmdt: 32
+    "#;
+
+    #[test]
+    fn test_evaluate_root() {
+        assert_eq!(evaluate_root(SAMPLE), Ok(152));
+    }
+
+    #[test]
+    fn test_solve_for_human() {
+        assert_eq!(solve_for_human(SAMPLE), Ok(301));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render_equation(SAMPLE),
+            Ok("((4 + (2 * (humn - 3))) / 4) = 150".to_string())
+        );
+        assert_eq!(
+            render_tree("root: a / humn\na: 3\nhumn: 2"),
+            Ok("(3 / humn)".to_string())
+        );
+    }
+
+    // The human on the right of - and / needs the inverse the other way round.
+    #[test_case("root: b + a\na: n - humn\nb: 2\nn: 7\nhumn: 0", 5)]
+    #[test_case("root: b + a\na: n / humn\nb: 4\nn: 12\nhumn: 1", 3)]
+    #[test_case("root: b + a\na: humn / n\nb: 4\nn: 3\nhumn: 1", 12)]
+    #[test_case("root: b + a\na: humn - n\nb: 4\nn: 3\nhumn: 1", 7)]
+    fn test_solve_either_side(input: &str, expected: i128) {
+        assert_eq!(solve_for_human(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_fractional_and_invalid_answers() {
+        let fraction = "root: a + b\na: humn * two\nb: 3\nhumn: 0\ntwo: 2";
+        assert_eq!(
+            solve_for_human(fraction),
+            Err("humn would have to yell 3/2".to_string())
+        );
+        let both_sides = "root: humn + humn\nhumn: 1";
+        assert!(solve_for_human(both_sides).is_err());
+        assert!(evaluate_root("root: a + b\na: 1")
+            .unwrap_err()
+            .contains("No monkey named b"));
+        assert!(evaluate_root("root: a + b\na: root * b\nb: 1")
+            .unwrap_err()
+            .contains("itself"));
+        assert!(evaluate_root("root: a / b\na: 1\nb: 0")
+            .unwrap_err()
+            .contains("Division by zero"));
+        let huge = "root: a * a\na: 100000000000000000000";
+        assert_eq!(
+            evaluate_root(huge),
+            Err("100000000000000000000 * 100000000000000000000 overflows".to_string())
+        );
+        let overflowing_target =
+            "root: b + a\na: humn / n\nb: 100000000000000000000\nn: 100000000000000000000\nhumn: 0";
+        assert!(solve_for_human(overflowing_target)
+            .unwrap_err()
+            .contains("overflows"));
+    }
+}
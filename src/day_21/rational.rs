@@ -0,0 +1,131 @@
+use std::fmt;
+
+// An exact fraction, always stored in lowest terms with a positive
+// denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    // None for a zero denominator, or one that can't be made positive.
+    pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        return Some(Self {
+            numerator: numerator.checked_div(divisor)?,
+            denominator: denominator.checked_div(divisor)?,
+        });
+    }
+
+    pub fn from_integer(value: i128) -> Self {
+        return Self {
+            numerator: value,
+            denominator: 1,
+        };
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        return (self.denominator == 1).then_some(self.numerator);
+    }
+
+    // None on overflow.
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        return Rational::new(
+            self.numerator
+                .checked_mul(other.denominator)?
+                .checked_add(other.numerator.checked_mul(self.denominator)?)?,
+            self.denominator.checked_mul(other.denominator)?,
+        );
+    }
+
+    // None on overflow.
+    pub fn checked_sub(self, other: Rational) -> Option<Rational> {
+        let negated = Rational {
+            numerator: other.numerator.checked_neg()?,
+            denominator: other.denominator,
+        };
+        return self.checked_add(negated);
+    }
+
+    // None on overflow.
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        return Rational::new(
+            self.numerator.checked_mul(other.numerator)?,
+            self.denominator.checked_mul(other.denominator)?,
+        );
+    }
+
+    // None when dividing by zero or on overflow.
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        return Rational::new(
+            self.numerator.checked_mul(other.denominator)?,
+            self.denominator.checked_mul(other.numerator)?,
+        );
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            return write!(f, "{}", self.numerator);
+        }
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    return a.checked_abs().unwrap_or(1).max(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rational_arithmetic() {
+        let third = Rational::new(2, 6).unwrap();
+        assert_eq!(third, Rational::new(-1, -3).unwrap());
+        assert_eq!(third.to_string(), "1/3");
+        assert_eq!(third.to_integer(), None);
+        assert_eq!(
+            third.checked_mul(Rational::from_integer(3)),
+            Some(Rational::from_integer(1))
+        );
+        assert_eq!(
+            third
+                .checked_sub(Rational::from_integer(1))
+                .unwrap()
+                .to_string(),
+            "-2/3"
+        );
+        assert_eq!(
+            third.checked_add(Rational::new(1, 6).unwrap()),
+            Rational::new(1, 2)
+        );
+        assert_eq!(
+            third.checked_div(Rational::new(-1, 6).unwrap()),
+            Some(Rational::from_integer(-2))
+        );
+        assert_eq!(third.checked_div(Rational::from_integer(0)), None);
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(Rational::new(0, -5), Some(Rational::from_integer(0)));
+    }
+
+    #[test]
+    fn test_rational_overflow() {
+        let big = Rational::from_integer(i128::MAX);
+        let half = Rational::new(1, 2).unwrap();
+        assert_eq!(big.checked_add(Rational::from_integer(1)), None);
+        assert_eq!(big.checked_mul(Rational::from_integer(2)), None);
+        assert_eq!(Rational::from_integer(i128::MIN).checked_sub(half), None);
+        assert_eq!(Rational::new(1, 3).unwrap().checked_div(big), None);
+        assert_eq!(Rational::new(1, i128::MIN), None);
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::grid::{Grid, Point, Point3};
+
+// Indexed by facing, which is also what the password counts with.
+const DIRECTIONS: [Point; 4] = [Point::RIGHT, Point::DOWN, Point::LEFT, Point::UP];
+
+pub fn final_password(input: &str) -> Result<i64, String> {
+    let (board, path) = parse_notes(input)?;
+    return Ok(board.walk(&path, &FlatWrap));
+}
+
+pub fn final_password_on_cube(input: &str) -> Result<i64, String> {
+    let (board, path) = parse_notes(input)?;
+    let cube = CubeWrap::fold(&board)?;
+    return Ok(board.walk(&path, &cube));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Void,
+    Open,
+    Wall,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Forward(u32),
+    Left,
+    Right,
+}
+
+// Where a walker ends up after stepping off the edge of the map. It is only
+// called when `from` plus the facing's direction isn't on the map.
+trait Wrapping {
+    fn wrap(&self, board: &Board, from: Point, facing: usize) -> (Point, usize);
+}
+
+// Comes back in on the far side of the same row or column.
+struct FlatWrap;
+
+impl Wrapping for FlatWrap {
+    fn wrap(&self, board: &Board, from: Point, facing: usize) -> (Point, usize) {
+        let mut point = from;
+        while board.is_on(point - DIRECTIONS[facing]) {
+            point = point - DIRECTIONS[facing];
+        }
+        return (point, facing);
+    }
+}
+
+// How a face of the net sits on the folded cube: its outward normal and
+// where its right and down directions point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Frame {
+    normal: Point3,
+    right: Point3,
+    down: Point3,
+}
+
+impl Frame {
+    fn axis(&self, facing: usize) -> Point3 {
+        return [self.right, self.down, -self.right, -self.down][facing];
+    }
+
+    // The frame of the face next to this one in the net, folded down over
+    // the shared edge.
+    fn across(&self, facing: usize) -> Frame {
+        let Frame {
+            normal,
+            right,
+            down,
+        } = *self;
+        return match facing {
+            0 => Frame {
+                normal: right,
+                right: -normal,
+                down,
+            },
+            1 => Frame {
+                normal: down,
+                right,
+                down: -normal,
+            },
+            2 => Frame {
+                normal: -right,
+                right: normal,
+                down,
+            },
+            _ => Frame {
+                normal: -down,
+                right,
+                down: normal,
+            },
+        };
+    }
+}
+
+// Folds the map into a cube. Faces are `size` tiles square and keyed by
+// their block position in the net, so (1, 0) is the second face along.
+struct CubeWrap {
+    size: i64,
+    frames: HashMap<Point, Frame>,
+    by_normal: HashMap<Point3, Point>,
+}
+
+impl CubeWrap {
+    fn fold(board: &Board) -> Result<CubeWrap, String> {
+        let tiles = board
+            .tiles
+            .iter()
+            .filter(|(_, t)| **t != Tile::Void)
+            .count();
+        let size = ((tiles / 6) as f64).sqrt().round() as usize;
+        if size == 0 || 6 * size * size != tiles {
+            return Err(format!("{} tiles can't make a cube", tiles));
+        }
+
+        let mut blocks = Vec::new();
+        for by in 0..board.tiles.height().div_ceil(size) {
+            for bx in 0..board.tiles.width().div_ceil(size) {
+                let corner = Point::from_unsigned(bx * size, by * size);
+                let filled = (0..size * size)
+                    .filter(|i| board.is_on(corner + Point::from_unsigned(i % size, i / size)))
+                    .count();
+                if filled == size * size {
+                    blocks.push(Point::from_unsigned(bx, by));
+                } else if filled > 0 {
+                    return Err(format!("Face at {} is only partly filled", corner));
+                }
+            }
+        }
+
+        let mut frames = HashMap::from([(
+            blocks[0],
+            Frame {
+                normal: Point3::new(0, 0, -1),
+                right: Point3::new(1, 0, 0),
+                down: Point3::new(0, 1, 0),
+            },
+        )]);
+        let mut to_visit = VecDeque::from([blocks[0]]);
+        while let Some(block) = to_visit.pop_front() {
+            for (facing, direction) in DIRECTIONS.iter().enumerate() {
+                let next = block + *direction;
+                if blocks.contains(&next) && !frames.contains_key(&next) {
+                    frames.insert(next, frames[&block].across(facing));
+                    to_visit.push_back(next);
+                }
+            }
+        }
+
+        let by_normal: HashMap<Point3, Point> =
+            frames.iter().map(|(b, f)| (f.normal, *b)).collect();
+        if frames.len() != 6 || by_normal.len() != 6 {
+            return Err("The map doesn't fold into a cube".to_string());
+        }
+        return Ok(CubeWrap {
+            size: size as i64,
+            frames,
+            by_normal,
+        });
+    }
+}
+
+impl Wrapping for CubeWrap {
+    fn wrap(&self, _board: &Board, from: Point, facing: usize) -> (Point, usize) {
+        let block = Point::new(from.x.div_euclid(self.size), from.y.div_euclid(self.size));
+        let local = from - block * self.size;
+        let frame = self.frames[&block];
+        let to_block = self.by_normal[&frame.axis(facing)];
+        let to_frame = self.frames[&to_block];
+        // Heading over the edge means heading back along the old normal.
+        let to_facing = (0..4).find(|f| to_frame.axis(*f) == -frame.normal).unwrap();
+
+        // Walking over a fold keeps what's on your right, so the distance
+        // along the edge from your left is the same on both faces.
+        let last = self.size - 1;
+        let from_left = match facing {
+            0 => local.y,
+            1 => last - local.x,
+            2 => last - local.y,
+            _ => local.x,
+        };
+        let entry = match to_facing {
+            0 => Point::new(0, from_left),
+            1 => Point::new(last - from_left, 0),
+            2 => Point::new(last, last - from_left),
+            _ => Point::new(from_left, last),
+        };
+        return (to_block * self.size + entry, to_facing);
+    }
+}
+
+struct Board {
+    tiles: Grid<Tile>,
+}
+
+impl Board {
+    fn is_on(&self, point: Point) -> bool {
+        return self.tiles.get(point).is_some_and(|t| *t != Tile::Void);
+    }
+
+    fn start(&self) -> Point {
+        return self
+            .tiles
+            .points()
+            .find(|p| self.tiles[*p] == Tile::Open)
+            .unwrap();
+    }
+
+    fn walk(&self, path: &[Step], wrapping: &impl Wrapping) -> i64 {
+        let mut position = self.start();
+        let mut facing = 0;
+        for step in path {
+            match step {
+                Step::Left => facing = (facing + 3) % 4,
+                Step::Right => facing = (facing + 1) % 4,
+                Step::Forward(count) => {
+                    for _ in 0..*count {
+                        let ahead = position + DIRECTIONS[facing];
+                        let (next, next_facing) = if self.is_on(ahead) {
+                            (ahead, facing)
+                        } else {
+                            wrapping.wrap(self, position, facing)
+                        };
+                        if self.tiles[next] == Tile::Wall {
+                            break;
+                        }
+                        (position, facing) = (next, next_facing);
+                    }
+                }
+            }
+        }
+        return 1000 * (position.y + 1) + 4 * (position.x + 1) + facing as i64;
+    }
+}
+
+// The map keeps its leading spaces, so only blank lines before it are
+// skipped.
+fn parse_notes(input: &str) -> Result<(Board, Vec<Step>), String> {
+    let input = input.replace("\r\n", "\n");
+    let (map, path) = input
+        .trim_start_matches('\n')
+        .split_once("\n\n")
+        .ok_or("Expected the map, a blank line, then the path")?;
+    return Ok((parse_board(map)?, parse_path(path.trim())?));
+}
+
+fn parse_board(map: &str) -> Result<Board, String> {
+    let lines: Vec<&str> = map.lines().map(str::trim_end).collect();
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let mut rows = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let mut row = line
+            .chars()
+            .map(|c| match c {
+                ' ' => Ok(Tile::Void),
+                '.' => Ok(Tile::Open),
+                '#' => Ok(Tile::Wall),
+                _ => Err(format!("Line {} has an unknown tile {:?}", i + 1, c)),
+            })
+            .collect::<Result<Vec<Tile>, String>>()?;
+        row.resize(width, Tile::Void);
+        rows.push(row);
+    }
+    let board = Board {
+        tiles: Grid::from_rows(rows)?,
+    };
+    if !board.tiles.iter().any(|(_, t)| *t == Tile::Open) {
+        return Err("The map has no open tiles".to_string());
+    }
+    return Ok(board);
+}
+
+// "10R5L5R10L4R5L5"
+fn parse_path(path: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    let mut count = String::new();
+    // Only digits are collected, so the parse fails only when too large.
+    let forward = |count: &str| {
+        return count
+            .parse()
+            .map(Step::Forward)
+            .map_err(|_| format!("Step count {} in the path is too large", count));
+    };
+    for c in path.chars() {
+        if c.is_ascii_digit() {
+            count.push(c);
+            continue;
+        }
+        if !count.is_empty() {
+            steps.push(forward(&count)?);
+            count.clear();
+        }
+        steps.push(match c {
+            'L' => Step::Left,
+            'R' => Step::Right,
+            _ => return Err(format!("Unknown step {:?} in the path", c)),
+        });
+    }
+    if !count.is_empty() {
+        steps.push(forward(&count)?);
+    }
+    return Ok(steps);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    // Leading spaces are part of the map, so this can't be indented.
+    const SAMPLE: &str = r#"
+        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+"#;
+
+    // The sample's layout and the one real inputs use, plus a cross.
+    const SAMPLE_NET: &[&str] = &["  # ", "### ", "  ##"];
+    const INPUT_NET: &[&str] = &[" ##", " # ", "## ", "#  "];
+    const CROSS_NET: &[&str] = &[" # ", "###", " # ", " # "];
+
+    // A board with no walls, made of `size` square faces laid out like `net`.
+    fn open_board(net: &[&str], size: usize) -> Board {
+        let mut map = String::new();
+        for line in net {
+            let row: String = line
+                .chars()
+                .map(|c| if c == '#' { "." } else { " " }.repeat(size))
+                .collect();
+            map.push_str(&format!("{}\n", row).repeat(size));
+        }
+        return parse_board(&map).unwrap();
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("10R5L"),
+            Ok(vec![
+                Step::Forward(10),
+                Step::Right,
+                Step::Forward(5),
+                Step::Left
+            ])
+        );
+        assert!(parse_path("10X").is_err());
+        assert_eq!(
+            parse_path("1R99999999999"),
+            Err("Step count 99999999999 in the path is too large".to_string())
+        );
+        assert!(parse_path("99999999999L1").is_err());
+    }
+
+    #[test]
+    fn test_final_password() {
+        assert_eq!(final_password(SAMPLE), Ok(6032));
+    }
+
+    #[test]
+    fn test_final_password_on_cube() {
+        assert_eq!(final_password_on_cube(SAMPLE), Ok(5031));
+    }
+
+    // Stepping off an edge and straight back must land where we started.
+    #[test_case(SAMPLE_NET, 4)]
+    #[test_case(INPUT_NET, 50)]
+    #[test_case(CROSS_NET, 3)]
+    fn test_cube_edges_pair_up(net: &[&str], size: usize) {
+        let board = open_board(net, size);
+        let cube = CubeWrap::fold(&board).unwrap();
+        for point in board.tiles.points().filter(|p| board.is_on(*p)) {
+            for (facing, direction) in DIRECTIONS.iter().enumerate() {
+                if board.is_on(point + *direction) {
+                    continue;
+                }
+                let (to, to_facing) = cube.wrap(&board, point, facing);
+                assert!(board.is_on(to));
+                assert_eq!(
+                    cube.wrap(&board, to, (to_facing + 2) % 4),
+                    (point, (facing + 2) % 4)
+                );
+            }
+        }
+    }
+
+    // Going straight on any cube goes round four faces and back.
+    #[test_case(SAMPLE_NET, 4)]
+    #[test_case(INPUT_NET, 50)]
+    #[test_case(CROSS_NET, 3)]
+    fn test_walk_around_cube(net: &[&str], size: usize) {
+        let board = open_board(net, size);
+        let cube = CubeWrap::fold(&board).unwrap();
+        let lap = Step::Forward(4 * size as u32);
+        let start = board.walk(&[], &cube);
+        assert_eq!(board.walk(&[lap], &cube), start);
+        assert_eq!(board.walk(&[Step::Right, lap, Step::Left], &cube), start);
+    }
+
+    #[test_case(&["##", "##", "# "]; "five faces")]
+    #[test_case(&["####", "##  "]; "overlapping faces")]
+    fn test_not_a_cube(net: &[&str]) {
+        assert!(CubeWrap::fold(&open_board(net, 2)).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(final_password("...\n").is_err());
+        assert!(final_password("..x\n\n1")
+            .unwrap_err()
+            .starts_with("Line 1"));
+    }
+}
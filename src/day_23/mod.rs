@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::{Grid, Point};
+
+pub fn count_empty_ground(input: &str) -> Result<usize, String> {
+    let mut elves = Elves::parse(input)?;
+    for _ in 0..10 {
+        elves.play_round();
+    }
+    let (low, high) = elves.bounding_box();
+    let area = (high.x - low.x + 1) * (high.y - low.y + 1);
+    return Ok(area as usize - elves.positions.len());
+}
+
+// The number of the first round in which nobody moves.
+pub fn first_still_round(input: &str) -> Result<usize, String> {
+    let mut elves = Elves::parse(input)?;
+    let mut round = 1;
+    while elves.play_round() {
+        round += 1;
+    }
+    return Ok(round);
+}
+
+pub fn render_after_rounds(input: &str, rounds: usize) -> Result<String, String> {
+    let mut elves = Elves::parse(input)?;
+    for _ in 0..rounds {
+        elves.play_round();
+    }
+    return Ok(elves.render());
+}
+
+// Each direction an elf may propose, with the three tiles that must be
+// empty for it to go that way.
+const PROPOSALS: [(Point, [Point; 3]); 4] = [
+    (
+        Point::UP,
+        [Point::new(-1, -1), Point::UP, Point::new(1, -1)],
+    ),
+    (
+        Point::DOWN,
+        [Point::new(-1, 1), Point::DOWN, Point::new(1, 1)],
+    ),
+    (
+        Point::LEFT,
+        [Point::new(-1, -1), Point::LEFT, Point::new(-1, 1)],
+    ),
+    (
+        Point::RIGHT,
+        [Point::new(1, -1), Point::RIGHT, Point::new(1, 1)],
+    ),
+];
+
+// Only occupied tiles are stored, since the elves spread out without
+// any edge to stop them.
+struct Elves {
+    positions: HashSet<Point>,
+    // Which of PROPOSALS is considered first this round.
+    first_proposal: usize,
+}
+
+impl Elves {
+    fn parse(input: &str) -> Result<Elves, String> {
+        let grid = Grid::parse_with(input, |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(format!("Unknown tile {:?}", c)),
+        })?;
+        let positions: HashSet<Point> = grid.iter().filter(|(_, e)| **e).map(|(p, _)| p).collect();
+        if positions.is_empty() {
+            return Err("No elves on the map".to_string());
+        }
+        return Ok(Elves {
+            positions,
+            first_proposal: 0,
+        });
+    }
+
+    // Returns whether any elf moved.
+    fn play_round(&mut self) -> bool {
+        // Proposed tile to the elf that wants it, or None once two do.
+        let mut proposals: HashMap<Point, Option<Point>> = HashMap::new();
+        for elf in &self.positions {
+            if let Some(target) = self.proposal(*elf) {
+                proposals
+                    .entry(target)
+                    .and_modify(|from| *from = None)
+                    .or_insert(Some(*elf));
+            }
+        }
+
+        let mut moved = false;
+        for (target, from) in proposals {
+            if let Some(from) = from {
+                self.positions.remove(&from);
+                self.positions.insert(target);
+                moved = true;
+            }
+        }
+        self.first_proposal = (self.first_proposal + 1) % PROPOSALS.len();
+        return moved;
+    }
+
+    // Elves with nobody around them stay put.
+    fn proposal(&self, elf: Point) -> Option<Point> {
+        if !elf.neighbors8().iter().any(|n| self.positions.contains(n)) {
+            return None;
+        }
+        return (0..PROPOSALS.len())
+            .map(|i| PROPOSALS[(self.first_proposal + i) % PROPOSALS.len()])
+            .find(|(_, checks)| checks.iter().all(|c| !self.positions.contains(&(elf + *c))))
+            .map(|(direction, _)| elf + direction);
+    }
+
+    // The top-left and bottom-right corners of the smallest rectangle
+    // holding every elf.
+    fn bounding_box(&self) -> (Point, Point) {
+        let xs = self.positions.iter().map(|p| p.x);
+        let ys = self.positions.iter().map(|p| p.y);
+        return (
+            Point::new(xs.clone().min().unwrap(), ys.clone().min().unwrap()),
+            Point::new(xs.max().unwrap(), ys.max().unwrap()),
+        );
+    }
+
+    fn render(&self) -> String {
+        let (low, high) = self.bounding_box();
+        let size = high - low + Point::new(1, 1);
+        let mut grid = Grid::new(size.x as usize, size.y as usize, '.');
+        for elf in &self.positions {
+            grid[*elf - low] = '#';
+        }
+        return grid.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = r#"
+        .....
+        ..##.
+        ..#..
+        .....
+        ..##.
+        .....
+    "#;
+
+    const SAMPLE: &str = r#"
+        ....#..
+        ..###.#
+        #...#.#
+        .#...##
+        #.###..
+        ##.#.##
+        .#..#..
+    "#;
+
+    #[test]
+    fn test_render_small() {
+        assert_eq!(
+            render_after_rounds(SMALL, 0),
+            Ok("##\n#.\n..\n##\n".to_string())
+        );
+        assert_eq!(
+            render_after_rounds(SMALL, 3),
+            Ok("..#..\n....#\n#....\n....#\n.....\n..#..\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_sample() {
+        let expected = r#"
+            ......#.....
+            ..........#.
+            .#.#..#.....
+            .....#......
+            ..#.....#..#
+            #......##...
+            ....##......
+            .#........#.
+            ...#.#..#...
+            ............
+            ...#..#..#..
+        "#;
+        let expected: Vec<&str> = expected.trim().lines().map(str::trim).collect();
+        let rendered = render_after_rounds(SAMPLE, 10).unwrap();
+        assert_eq!(rendered.lines().collect::<Vec<&str>>(), expected);
+    }
+
+    #[test]
+    fn test_count_empty_ground() {
+        assert_eq!(count_empty_ground(SAMPLE), Ok(110));
+    }
+
+    #[test]
+    fn test_first_still_round() {
+        assert_eq!(first_still_round(SMALL), Ok(4));
+        assert_eq!(first_still_round(SAMPLE), Ok(20));
+        assert_eq!(first_still_round("#"), Ok(1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(count_empty_ground("...").is_err());
+        assert!(count_empty_ground("#x").is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use std::{
+    fmt,
+    ops::{Add, Index, IndexMut, Mul, Neg, Sub},
+};
+
+// A signed 2D position or offset. Grids index with x to the right and y
+// downwards, the way puzzle input is laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const UP: Point = Point { x: 0, y: -1 };
+    pub const DOWN: Point = Point { x: 0, y: 1 };
+    pub const LEFT: Point = Point { x: -1, y: 0 };
+    pub const RIGHT: Point = Point { x: 1, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        return Self { x, y };
+    }
+
+    pub fn from_unsigned(x: usize, y: usize) -> Self {
+        return Self::new(x as i64, y as i64);
+    }
+
+    // None when either coordinate is negative.
+    pub fn to_unsigned(self) -> Option<(usize, usize)> {
+        return Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?));
+    }
+
+    pub fn manhattan_distance(self, other: Point) -> i64 {
+        return (self.x - other.x).abs() + (self.y - other.y).abs();
+    }
+
+    // Number of king moves between the points.
+    pub fn chebyshev_distance(self, other: Point) -> i64 {
+        return (self.x - other.x).abs().max((self.y - other.y).abs());
+    }
+
+    // Each coordinate clamped to -1, 0 or 1.
+    pub fn signum(self) -> Point {
+        return Point::new(self.x.signum(), self.y.signum());
+    }
+
+    pub fn neighbors4(self) -> [Point; 4] {
+        return [
+            self + Point::UP,
+            self + Point::RIGHT,
+            self + Point::DOWN,
+            self + Point::LEFT,
+        ];
+    }
+
+    pub fn neighbors8(self) -> [Point; 8] {
+        let mut neighbors = [self; 8];
+        let mut i = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx != 0 || dy != 0 {
+                    neighbors[i] = self + Point::new(dx, dy);
+                    i += 1;
+                }
+            }
+        }
+        return neighbors;
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        return Point::new(self.x + other.x, self.y + other.y);
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        return Point::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: i64) -> Point {
+        return Point::new(self.x * factor, self.y * factor);
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        return Self::from_unsigned(x, y);
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+// A unit cube position in 3D space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3 {
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        return Self { x, y, z };
+    }
+
+    // The six points sharing a face with this one.
+    pub fn neighbors6(self) -> [Point3; 6] {
+        return [
+            self + Point3::new(1, 0, 0),
+            self + Point3::new(-1, 0, 0),
+            self + Point3::new(0, 1, 0),
+            self + Point3::new(0, -1, 0),
+            self + Point3::new(0, 0, 1),
+            self + Point3::new(0, 0, -1),
+        ];
+    }
+
+    // Smallest value of each coordinate across both points.
+    pub fn min_each(self, other: Point3) -> Point3 {
+        return Point3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        );
+    }
+
+    pub fn max_each(self, other: Point3) -> Point3 {
+        return Point3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        );
+    }
+}
+
+impl Add for Point3 {
+    type Output = Point3;
+
+    fn add(self, other: Point3) -> Point3 {
+        return Point3::new(self.x + other.x, self.y + other.y, self.z + other.z);
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Point3;
+
+    fn sub(self, other: Point3) -> Point3 {
+        return Point3::new(self.x - other.x, self.y - other.y, self.z - other.z);
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Point3;
+
+    fn neg(self) -> Point3 {
+        return Point3::new(-self.x, -self.y, -self.z);
+    }
+}
+
+impl fmt::Display for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+// A rectangular grid stored row by row in one Vec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        return Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        };
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, String> {
+        let width = rows.first().map_or(0, |r| r.len());
+        if let Some(y) = rows.iter().position(|r| r.len() != width) {
+            return Err(format!(
+                "Row {} has {} cells, expected {}",
+                y + 1,
+                rows[y].len(),
+                width
+            ));
+        }
+        return Ok(Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        });
+    }
+
+    // One row per line, surrounding whitespace ignored. Blank lines before
+    // and after the grid are dropped, but one inside it is an empty row and
+    // fails the width check.
+    pub fn parse_with<F: Fn(char) -> Result<T, String>>(
+        text: &str,
+        parse_cell: F,
+    ) -> Result<Self, String> {
+        let rows = text
+            .trim()
+            .lines()
+            .map(str::trim)
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .map(|c| parse_cell(c).map_err(|e| format!("Line {}: {}", y + 1, e)))
+                    .collect::<Result<Vec<T>, String>>()
+            })
+            .collect::<Result<Vec<Vec<T>>, String>>()?;
+        return Self::from_rows(rows);
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        return self.index_of(point).is_some();
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        return self.index_of(point).map(|i| &self.cells[i]);
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        return self.index_of(point).map(|i| &mut self.cells[i]);
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        return &self.cells[y * self.width..(y + 1) * self.width];
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        return self.cells.chunks(self.width.max(1)).take(self.height);
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(x < self.width, "Column {} outside width {}", x, self.width);
+        return self.cells.iter().skip(x).step_by(self.width);
+    }
+
+    // Every point, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        return (0..self.height)
+            .flat_map(move |y| (0..width).map(move |x| Point::from_unsigned(x, y)));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        return self.points().zip(self.cells.iter());
+    }
+
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<Point> {
+        return self.iter().find(|(_, val)| predicate(val)).map(|(p, _)| p);
+    }
+
+    // Up, right, down, left, skipping anything off the grid.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        return point.neighbors4().into_iter().filter(|p| self.contains(*p));
+    }
+
+    // All eight surrounding points, diagonals included, skipping anything
+    // off the grid.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        return point.neighbors8().into_iter().filter(|p| self.contains(*p));
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid<U> {
+        return Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        };
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        let (x, y) = point.to_unsigned()?;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        return Some(y * self.width + x);
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        return Self::parse_with(text, Ok);
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        return self
+            .get(point)
+            .unwrap_or_else(|| panic!("{} is outside the grid", point));
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        return self
+            .get_mut(point)
+            .unwrap_or_else(|| panic!("{} is outside the grid", point));
+    }
+}
+
+// Cells side by side with a newline after every row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const SAMPLE: &str = r#"
+        abc
+        def
+    "#;
+
+    #[test]
+    fn test_parse_and_views() {
+        let grid = Grid::parse(SAMPLE).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid[Point::new(1, 1)], 'e');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.find(|c| *c == 'f'), Some(Point::new(2, 1)));
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Grid::parse("ab\nc").unwrap_err().contains("Row 2"));
+        let digits = Grid::parse_with("12\n3x", |c| c.to_digit(10).ok_or(format!("{:?}", c)));
+        assert_eq!(digits, Err("Line 2: 'x'".to_string()));
+        assert!(Grid::parse("\n  ab\n  cd\n\n").is_ok());
+        assert!(Grid::parse("ab\n\ncd").unwrap_err().contains("Row 2"));
+    }
+
+    #[test_case(Point::new(0, 0), 2, 3)]
+    #[test_case(Point::new(1, 0), 3, 5)]
+    #[test_case(Point::new(1, 1), 3, 5)]
+    #[test_case(Point::new(5, 5), 0, 0)]
+    fn test_neighbors(point: Point, expected_4: usize, expected_8: usize) {
+        let grid = Grid::parse(SAMPLE).unwrap();
+        assert_eq!(grid.neighbors4(point).count(), expected_4);
+        assert_eq!(grid.neighbors8(point).count(), expected_8);
+    }
+
+    #[test]
+    fn test_neighbors8_stay_on_grid() {
+        let grid = Grid::parse(SAMPLE).unwrap();
+        let around_corner: String = grid.neighbors8(Point::new(2, 1)).map(|p| grid[p]).collect();
+        assert_eq!(around_corner, "bce");
+        // Just past the corner only touches it diagonally.
+        let outside: Vec<Point> = grid.neighbors8(Point::new(3, 2)).collect();
+        assert_eq!(outside, vec![Point::new(2, 1)]);
+    }
+
+    #[test]
+    fn test_point_conversions() {
+        let p = Point::from((3, 4));
+        assert_eq!(p.to_unsigned(), Some((3, 4)));
+        assert_eq!(Point::new(-1, 4).to_unsigned(), None);
+        assert_eq!(p - Point::new(5, 0), Point::new(-2, 4));
+        assert_eq!(Point::new(-7, 2).signum(), Point::new(-1, 1));
+        assert_eq!(p.manhattan_distance(Point::default()), 7);
+        assert_eq!(p.chebyshev_distance(Point::default()), 4);
+        assert_eq!(Point::RIGHT * 3, Point::new(3, 0));
+    }
+
+    #[test]
+    fn test_point3() {
+        let p = Point3::new(1, -2, 3);
+        assert_eq!(p.neighbors6().len(), 6);
+        assert!(p.neighbors6().contains(&Point3::new(1, -2, 2)));
+        assert_eq!(p.min_each(Point3::default()), Point3::new(0, -2, 0));
+        assert_eq!(p.max_each(Point3::default()), Point3::new(1, 0, 3));
+        assert_eq!(p - p, Point3::default());
+    }
+
+    #[test]
+    fn test_map_and_mutate() {
+        let mut grid = Grid::new(2, 2, 0u8).map(|v| v + 1);
+        grid[Point::new(1, 0)] = 9;
+        *grid.get_mut(Point::new(0, 1)).unwrap() = 5;
+        assert_eq!(grid.to_string(), "19\n51\n");
+        assert_eq!(grid.iter().map(|(_, v)| *v as u32).sum::<u32>(), 16);
+    }
+}
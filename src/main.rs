@@ -1,19 +1,51 @@
 use std::io::{self, Read};
 
 use crate::{
-    day_01::{day_01, day_01_b},
-    day_02::{rock_paper_scissors, rock_paper_scissors_pt2},
-    day_03::{calc_group_badge_totals, calc_total_of_high_priority},
-    day_04::{count_containing_pairs, count_overlapping_pairs},
-    day_05::{build_stacks, calculate_crane_moves, calculate_crate_mover_9001},
-    day_06::{calculate_first_marker, calculate_start_of_message_index},
+    day_01::{day_01, day_01_b, stream_top_calories, CalorieInventory},
+    day_02::{
+        analyze_optimal_play, analyze_strategy_guide, analyze_strategy_guide_pt2,
+        best_possible_score, rock_paper_scissors, rock_paper_scissors_pt2, RuleTable,
+    },
+    day_03::{
+        analyze_compartments, analyze_group_badges, calc_group_badge_totals,
+        calc_total_of_high_priority,
+    },
+    day_04::{
+        build_coverage, count_containing_pairs, count_elves_covering, count_overlapping_pairs,
+        find_uncovered_sections,
+    },
+    day_05::{
+        animate_crane, build_stacks, calculate_crane_moves, calculate_crate_mover_9001,
+        find_first_divergence, rebuild_start, render_stacks, run_crane, CapacityLimitedMover,
+        CraneHistory, CrateMover9000, CrateMover9001,
+    },
+    day_06::{
+        calculate_first_marker, calculate_start_of_message_index, find_unique_run_ends_in_reader,
+    },
     day_07::calculate_directory_size_sum,
-    day_08::{calc_max_senic_score, count_visible_trees},
+    day_08::{
+        calc_max_senic_score, count_visible_trees, find_best_tree, render_senic_score_pgm,
+        render_visibility_mask,
+    },
     day_09::{count_multi_knot_tail_position, count_tail_positions},
     day_10::{calculate_total_signal, parse_steps_into_ascii_art},
-    day_11::compute_input_inspection_product, day_12::{day_12_pt_1, day_12_pt_2}, day_13::sum_order_pair_indexes,
+    day_11::{
+        compute_input_inspection_product, cross_check_input_monkeys,
+        fast_forward_input_inspection_product, parse_relief, report_input_monkeys,
+    },
+    day_12::{day_12_pt_1, day_12_pt_2}, day_13::sum_order_pair_indexes,
+    day_14::{count_resting_sand, count_sand_until_source_blocked, render_filled_cave},
+    day_15::{calc_tuning_frequency, count_excluded_positions},
+    day_16::{max_pressure_alone, max_pressure_with_elephant},
+    day_17::{render_chamber_top, tower_height},
+    day_18::{calc_exterior_surface_area, calc_surface_area},
+    day_19::{best_build_order, multiply_first_three, sum_quality_levels},
+    day_20::{sum_decrypted_grove_coordinates, sum_grove_coordinates},
+    day_21::{evaluate_root, render_equation, render_tree, solve_for_human},
+    day_22::{final_password, final_password_on_cube},
+    day_23::{count_empty_ground, first_still_round, render_after_rounds},
 };
-use std::env;
+use std::{env, fmt::Display};
 
 mod day_01;
 mod day_02;
@@ -28,32 +60,144 @@ mod day_10;
 mod day_11;
 mod day_12;
 mod day_13;
+mod day_14;
+mod day_15;
+mod day_16;
+mod day_17;
+mod day_18;
+mod day_19;
+mod day_20;
+mod day_21;
+mod day_22;
+mod day_23;
+mod grid;
 
 fn main() -> io::Result<()> {
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
-
     let args: Vec<String> = env::args().collect();
     let day = &args[1];
 
     println!("Running for {}", day);
     let result = match day.as_str() {
-        "1" => day_01(buffer).to_string(),
-        "1b" => day_01_b(buffer).to_string(),
+        // These read stdin as it arrives instead of buffering it all
+        "1" => report(day_01(io::stdin().lock())),
+        "1b" => report(day_01_b(io::stdin().lock())),
+        "1top" => {
+            let n: usize = args[2].parse().expect("Count not parsable");
+            report(stream_top_calories(io::stdin().lock(), n).map(|top| {
+                join_lines(
+                    top.iter()
+                        .map(|(number, total)| format!("elf {}: {}", number, total))
+                        .collect(),
+                )
+            }))
+        }
+        "6all" => report(stream_unique_run_ends(4)),
+        "6ball" => report(stream_unique_run_ends(14)),
+        _ => run_with_buffered_input(day, &args)?,
+    };
+    println!("Result: {}", result);
+
+    Ok(())
+}
+
+fn run_with_buffered_input(day: &str, args: &[String]) -> io::Result<String> {
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer)?;
+
+    let result = match day {
+        "1stats" => {
+            let strict = args.get(2).is_some_and(|a| a == "strict");
+            report(
+                CalorieInventory::parse(buffer.as_str())
+                    .and_then(|i| if strict { i.validated() } else { Ok(i) })
+                    .map(|i| "\n".to_owned() + &i.to_string()),
+            )
+        }
         "2" => rock_paper_scissors(buffer).to_string(),
         "2b" => rock_paper_scissors_pt2(buffer).to_string(),
-        "3" => calc_total_of_high_priority(buffer.as_str()).to_string(),
-        "3b" => calc_group_badge_totals(buffer.as_str()).to_string(),
-        "4" => count_containing_pairs(buffer.as_str()).to_string(),
-        "4b" => count_overlapping_pairs(buffer.as_str()).to_string(),
-        "5" => calculate_crane_moves(buffer.as_str(), build_stacks()),
-        "5b" => calculate_crate_mover_9001(buffer.as_str(), build_stacks()),
-        "6" => calculate_first_marker(buffer.as_str()).to_string(),
-        "6b" => calculate_start_of_message_index(buffer.as_str()).to_string(),
+        "2report" => {
+            let rules = RuleTable::classic();
+            "\n".to_owned() + &analyze_strategy_guide(buffer.as_str(), &rules).to_string()
+        }
+        "2breport" => {
+            let rules = RuleTable::classic();
+            "\n".to_owned() + &analyze_strategy_guide_pt2(buffer.as_str(), &rules).to_string()
+        }
+        "2best" => best_possible_score(buffer.as_str(), &RuleTable::classic()).to_string(),
+        "2bestreport" => {
+            let rules = RuleTable::classic();
+            "\n".to_owned() + &analyze_optimal_play(buffer.as_str(), &rules).to_string()
+        }
+        "2rules" => report(
+            RuleTable::from_file(&args[2])
+                .map(|rules| "\n".to_owned() + &analyze_strategy_guide(buffer.as_str(), &rules).to_string()),
+        ),
+        "2brules" => report(
+            RuleTable::from_file(&args[2])
+                .map(|rules| "\n".to_owned() + &analyze_strategy_guide_pt2(buffer.as_str(), &rules).to_string()),
+        ),
+        "3" => report(calc_total_of_high_priority(buffer.as_str())),
+        "3b" => report(calc_group_badge_totals(buffer.as_str())),
+        "3items" => report(analyze_compartments(buffer.as_str()).map(join_lines)),
+        "3badges" => {
+            let size: usize = args.get(2).map_or(3, |n| n.parse().expect("Group size not parsable"));
+            report(analyze_group_badges(buffer.as_str(), size).map(join_lines))
+        }
+        "4" => report(count_containing_pairs(buffer.as_str())),
+        "4b" => report(count_overlapping_pairs(buffer.as_str())),
+        "4gaps" => report(find_uncovered_sections(buffer.as_str()).map(|gaps| format!("{:?}", gaps))),
+        "4cover" => report(build_coverage(buffer.as_str()).map(|set| {
+            format!("{} ({} sections in {} runs)", set, set.coverage(), set.ranges().len())
+        })),
+        "4share" => {
+            let section: u32 = args[2].parse().expect("Section not parsable");
+            report(count_elves_covering(buffer.as_str(), section))
+        }
+        "5" => report(calculate_crane_moves(buffer.as_str(), build_stacks())),
+        "5b" => report(calculate_crate_mover_9001(buffer.as_str(), build_stacks())),
+        "5cap" => {
+            let capacity: usize = args[2].parse().expect("Capacity not parsable");
+            let mover = CapacityLimitedMover { capacity };
+            report(run_crane(&mover, buffer.as_str(), build_stacks()))
+        }
+        "5anim" | "5banim" => {
+            let every: usize = args.get(2).map_or(1, |n| n.parse().expect("Step not parsable"));
+            let result = if day == "5anim" {
+                animate_crane(&CrateMover9000, buffer.as_str(), build_stacks(), every)
+            } else {
+                animate_crane(&CrateMover9001, buffer.as_str(), build_stacks(), every)
+            };
+            report(result.map(|frames| "\n".to_owned() + &frames))
+        }
+        "5diverge" => report(
+            find_first_divergence(&CrateMover9000, &CrateMover9001, buffer.as_str(), build_stacks())
+                .map(|step| format!("{:?}", step)),
+        ),
+        "5verify" => report(
+            CraneHistory::new(&CrateMover9001, buffer.as_str(), build_stacks()).and_then(|mut history| {
+                history.seek(history.move_count())?;
+                history.verify()?;
+                return Ok(format!("{} moves undo cleanly", history.position()));
+            }),
+        ),
+        "5rebuild" => report(
+            CraneHistory::new(&CrateMover9001, buffer.as_str(), build_stacks()).and_then(|mut history| {
+                history.seek(history.move_count())?;
+                let start = rebuild_start(&CrateMover9001, history.stacks(), buffer.as_str())?;
+                return Ok("\n".to_owned() + &render_stacks(&start));
+            }),
+        ),
+        "6" => report(calculate_first_marker(buffer.as_str()).ok_or("No start-of-packet marker".to_string())),
+        "6b" => report(
+            calculate_start_of_message_index(buffer.as_str()).ok_or("No start-of-message marker".to_string()),
+        ),
         "7" => calculate_directory_size_sum(buffer.as_str()).to_string(),
 
         "8" => count_visible_trees(buffer.as_str()).to_string(),
         "8b" => calc_max_senic_score(buffer.as_str()).to_string(),
+        "8best" => find_best_tree(buffer.as_str()).to_string(),
+        "8mask" => "\n".to_owned() + &render_visibility_mask(buffer.as_str()),
+        "8pgm" => "\n".to_owned() + &render_senic_score_pgm(buffer.as_str()),
 
         "9" => count_tail_positions(buffer.as_str()).to_string(),
         "9b" => count_multi_knot_tail_position(buffer.as_str()).to_string(),
@@ -61,17 +205,107 @@ fn main() -> io::Result<()> {
         "10" => calculate_total_signal(buffer.as_str()).to_string(),
         "10b" => "\n".to_owned() + &parse_steps_into_ascii_art(buffer.as_str()),
 
-        "11" => compute_input_inspection_product(20, 3).to_string(),
-        "11b" => compute_input_inspection_product(10000, 1).to_string(),
+        "11" => report(compute_input_inspection_product(20, 3)),
+        "11b" => report(compute_input_inspection_product(10000, 1)),
+        "11report" => {
+            let rounds: u32 = args[2].parse().expect("Rounds not parsable");
+            let every: u32 = args.get(4).map_or(1, |n| n.parse().expect("Step not parsable"));
+            report(parse_relief(&args[3]).map(|relief| {
+                "\n".to_owned() + &report_input_monkeys(rounds, relief.as_ref(), every).to_string()
+            }))
+        }
+        "11ff" => {
+            let rounds: u64 = args[2].parse().expect("Rounds not parsable");
+            report(fast_forward_input_inspection_product(rounds))
+        }
+        "11exact" => {
+            let rounds: u32 = args[2].parse().expect("Rounds not parsable");
+            let spec = args.get(3).map_or("1", |s| s.as_str());
+            report(parse_relief(spec).and_then(|relief| {
+                cross_check_input_monkeys(rounds, relief.as_ref())
+                    .map(|exact| "\n".to_owned() + &exact.to_string())
+            }))
+        }
 
         "12" => day_12_pt_1(buffer.as_str()).to_string(),
         "12b" => day_12_pt_2(buffer.as_str()).to_string(),
 
         "13" => sum_order_pair_indexes(buffer.as_str()).to_string(),
 
+        "14" => report(count_resting_sand(buffer.as_str())),
+        "14b" => report(count_sand_until_source_blocked(buffer.as_str())),
+        "14render" => report(render_filled_cave(buffer.as_str(), false).map(|c| "\n".to_owned() + &c)),
+        "14brender" => report(render_filled_cave(buffer.as_str(), true).map(|c| "\n".to_owned() + &c)),
+
+        "15" => {
+            let row: i64 = args.get(2).map_or(2_000_000, |r| r.parse().expect("Row not parsable"));
+            report(count_excluded_positions(buffer.as_str(), row))
+        }
+        "15b" => {
+            let bound: i64 = args.get(2).map_or(4_000_000, |b| b.parse().expect("Bound not parsable"));
+            report(calc_tuning_frequency(buffer.as_str(), bound))
+        }
+
+        "16" => report(max_pressure_alone(buffer.as_str())),
+        "16b" => report(max_pressure_with_elephant(buffer.as_str())),
+
+        "17" => report(tower_height(buffer.as_str(), 2022)),
+        "17b" => report(tower_height(buffer.as_str(), 1_000_000_000_000)),
+        "17render" => {
+            let rocks: usize = args[2].parse().expect("Rocks not parsable");
+            let rows: usize = args.get(3).map_or(20, |n| n.parse().expect("Rows not parsable"));
+            report(render_chamber_top(buffer.as_str(), rocks, rows).map(|c| "\n".to_owned() + &c))
+        }
+
+        "18" => report(calc_surface_area(buffer.as_str())),
+        "18b" => report(calc_exterior_surface_area(buffer.as_str())),
+
+        "19" => report(sum_quality_levels(buffer.as_str())),
+        "19b" => report(multiply_first_three(buffer.as_str())),
+        "19plan" => {
+            let id: u32 = args[2].parse().expect("Blueprint id not parsable");
+            let minutes: u32 = args.get(3).map_or(24, |m| m.parse().expect("Minutes not parsable"));
+            report(best_build_order(buffer.as_str(), id, minutes).map(|p| "\n".to_owned() + &p.to_string()))
+        }
+
+        "20" => report(sum_grove_coordinates(buffer.as_str())),
+        "20b" => report(sum_decrypted_grove_coordinates(buffer.as_str())),
+
+        "21" => report(evaluate_root(buffer.as_str())),
+        "21b" => report(solve_for_human(buffer.as_str())),
+        "21tree" => report(render_tree(buffer.as_str())),
+        "21equation" => report(render_equation(buffer.as_str())),
+
+        "22" => report(final_password(buffer.as_str())),
+        "22b" => report(final_password_on_cube(buffer.as_str())),
+
+        "23" => report(count_empty_ground(buffer.as_str())),
+        "23b" => report(first_still_round(buffer.as_str())),
+        "23render" => {
+            let rounds: usize = args.get(2).map_or(10, |r| r.parse().expect("Rounds not parsable"));
+            report(render_after_rounds(buffer.as_str(), rounds).map(|r| "\n".to_owned() + &r))
+        }
+
         _ => "unknown".to_string(),
     };
-    println!("Result: {}", result);
+    return Ok(result);
+}
 
-    Ok(())
+fn stream_unique_run_ends(length: usize) -> Result<String, String> {
+    let ends = find_unique_run_ends_in_reader(io::stdin().lock(), length)
+        .ok_or("Marker length must be at least 1".to_string())?
+        .collect::<io::Result<Vec<usize>>>()
+        .map_err(|e| e.to_string())?;
+    return Ok(format!("{:?}", ends));
+}
+
+fn report<T: Display>(result: Result<T, String>) -> String {
+    return match result {
+        Ok(val) => val.to_string(),
+        Err(e) => format!("Error: {}", e),
+    };
+}
+
+fn join_lines<T: Display>(items: Vec<T>) -> String {
+    return items.iter().map(|i| format!("\n{}", i)).collect();
 }